@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+
+pub type Map = Vec<Vec<char>>;
+
+pub fn read_map(map: &str) -> Map {
+    map.lines().map(|line| line.chars().collect()).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+/// The directions a pipe connects to.
+pub fn connections(pipe: char) -> &'static [Direction] {
+    match pipe {
+        '|' => &[Direction::Up, Direction::Down],
+        '-' => &[Direction::Left, Direction::Right],
+        'L' => &[Direction::Up, Direction::Right],
+        'J' => &[Direction::Up, Direction::Left],
+        '7' => &[Direction::Down, Direction::Left],
+        'F' => &[Direction::Down, Direction::Right],
+        _ => &[],
+    }
+}
+
+/// The pipe connecting exactly two directions.
+pub fn pipe_from_connections(a: Direction, b: Direction) -> char {
+    *['|', '-', 'L', 'J', '7', 'F']
+        .iter()
+        .find(|&&pipe| connections(pipe).contains(&a) && connections(pipe).contains(&b))
+        .unwrap_or_else(|| panic!("No pipe connects {:?} and {:?}", a, b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub y: usize,
+    pub x: usize,
+}
+
+impl Pos {
+    pub fn new(y: usize, x: usize) -> Self {
+        Self { y, x }
+    }
+
+    pub fn get_pipe(&self, map: &Map) -> char {
+        map[self.y][self.x]
+    }
+
+    /// The neighboring position in a direction, if it is inside the map.
+    pub fn step(&self, direction: Direction, map: &Map) -> Option<Pos> {
+        let (y, x) = match direction {
+            Direction::Up => (self.y.checked_sub(1)?, self.x),
+            Direction::Down => (self.y + 1, self.x),
+            Direction::Left => (self.y, self.x.checked_sub(1)?),
+            Direction::Right => (self.y, self.x + 1),
+        };
+        if y < map.len() && x < map[y].len() {
+            Some(Pos::new(y, x))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the pipe at this position connects to the pipe in the given
+    /// direction, and the other way around.
+    pub fn connects_to(&self, direction: Direction, map: &Map) -> bool {
+        connections(self.get_pipe(map)).contains(&direction)
+            && self.step(direction, map).is_some_and(|other| {
+                connections(other.get_pipe(map)).contains(&direction.opposite())
+            })
+    }
+}
+
+/// Follow the pipes from a position until coming back to it. Return `None`
+/// if the pipes don't form a closed loop, for instance if a pipe doesn't
+/// connect back or leads out of the map.
+fn trace_loop(map: &Map, from: Pos) -> Option<Vec<Pos>> {
+    let mut direction = *connections(from.get_pipe(map)).first()?;
+    let mut res = vec![from];
+    let mut current = from;
+    loop {
+        if !current.connects_to(direction, map) {
+            return None;
+        }
+        current = current.step(direction, map)?;
+        if current == from {
+            return Some(res);
+        }
+        res.push(current);
+        direction = *connections(current.get_pipe(map))
+            .iter()
+            .find(|x| **x != direction.opposite())?;
+    }
+}
+
+/// A map of pipes, where the starting tile has been replaced by the pipe it
+/// hides.
+pub struct PipeMaze {
+    pub map: Map,
+    pub start: Pos,
+}
+
+fn starting_position(map: &Map) -> Pos {
+    for (y, line) in map.iter().enumerate() {
+        for (x, c) in line.iter().enumerate() {
+            if *c == 'S' {
+                return Pos::new(y, x);
+            }
+        }
+    }
+    panic!("No starting position found");
+}
+
+impl PipeMaze {
+    /// Build the maze, replacing the starting tile by a pipe closing a loop
+    /// through it. When more than two neighbors connect to the starting
+    /// tile, every pair of them is tried, and the longest closed loop wins.
+    pub fn new(mut map: Map) -> PipeMaze {
+        let start = starting_position(&map);
+        let directions = Direction::ALL
+            .into_iter()
+            .filter(|direction| {
+                start.step(*direction, &map).is_some_and(|neighbor| {
+                    connections(neighbor.get_pipe(&map)).contains(&direction.opposite())
+                })
+            })
+            .collect::<Vec<Direction>>();
+
+        let mut best: Option<(char, usize)> = None;
+        for (i, a) in directions.iter().enumerate() {
+            for b in directions.iter().skip(i + 1) {
+                let pipe = pipe_from_connections(*a, *b);
+                map[start.y][start.x] = pipe;
+                if let Some(polygon) = trace_loop(&map, start) {
+                    if best.is_none_or(|(_, length)| polygon.len() > length) {
+                        best = Some((pipe, polygon.len()));
+                    }
+                }
+            }
+        }
+        let (pipe, _) = best.expect("No closed loop goes through the starting position");
+        map[start.y][start.x] = pipe;
+        PipeMaze { map, start }
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.map[0].len()
+    }
+
+    /// The loop going through the starting position, as the ordered list of
+    /// the tiles on it, starting with the starting position.
+    pub fn main_loop(&self) -> Vec<Pos> {
+        trace_loop(&self.map, self.start).expect("The loop is broken")
+    }
+
+    /// Every closed loop of the map. Loops are disjoint, since each pipe
+    /// connects to only two tiles.
+    pub fn find_loops(&self) -> Vec<Vec<Pos>> {
+        let mut visited = HashSet::new();
+        let mut res = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.map[y].len() {
+                let pos = Pos::new(y, x);
+                if visited.contains(&pos) {
+                    continue;
+                }
+                if let Some(polygon) = trace_loop(&self.map, pos) {
+                    visited.extend(polygon.iter().copied());
+                    res.push(polygon);
+                }
+            }
+        }
+        res
+    }
+
+    /// Print every loop of the map, and which one goes through the starting
+    /// position.
+    pub fn print_loops(&self) {
+        let loops = self.find_loops();
+        println!(
+            "Starting position ({}, {}) is a '{}'",
+            self.start.y,
+            self.start.x,
+            self.start.get_pipe(&self.map)
+        );
+        for (idx, polygon) in loops.iter().enumerate() {
+            println!(
+                "Loop {}: {} tiles from ({}, {}){}",
+                idx,
+                polygon.len(),
+                polygon[0].y,
+                polygon[0].x,
+                if polygon.contains(&self.start) {
+                    ", contains the starting position"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+
+    /// Number of tiles enclosed by the loop, computed from its area with the
+    /// shoelace formula, and Pick's theorem `A = i + b / 2 - 1`.
+    #[allow(dead_code)]
+    pub fn enclosed_count_pick(polygon: &[Pos]) -> usize {
+        let twice_area = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+            .sum::<i64>()
+            .abs();
+        let boundary = polygon.len() as i64;
+        ((twice_area - boundary + 2) / 2) as usize
+    }
+
+    /// Tiles enclosed by the loop, computed by a flood fill of the outside
+    /// on a map scaled up 3 times. Each tile becomes a 3x3 block where the
+    /// pipes of the loop are drawn as walls, so the fill can squeeze between
+    /// adjacent pipes.
+    #[allow(dead_code)]
+    pub fn enclosed_tiles_flood_fill(&self, polygon: &[Pos]) -> HashSet<Pos> {
+        let height = 3 * self.height();
+        let width = 3 * self.width();
+        let mut wall = vec![vec![false; width]; height];
+        for pos in polygon {
+            let (cy, cx) = (3 * pos.y + 1, 3 * pos.x + 1);
+            wall[cy][cx] = true;
+            for direction in connections(pos.get_pipe(&self.map)) {
+                match direction {
+                    Direction::Up => wall[cy - 1][cx] = true,
+                    Direction::Down => wall[cy + 1][cx] = true,
+                    Direction::Left => wall[cy][cx - 1] = true,
+                    Direction::Right => wall[cy][cx + 1] = true,
+                }
+            }
+        }
+
+        let mut outside = vec![vec![false; width]; height];
+        let mut stack = Vec::new();
+        for y in 0..height {
+            stack.push((y, 0));
+            stack.push((y, width - 1));
+        }
+        for x in 0..width {
+            stack.push((0, x));
+            stack.push((height - 1, x));
+        }
+        while let Some((y, x)) = stack.pop() {
+            if wall[y][x] || outside[y][x] {
+                continue;
+            }
+            outside[y][x] = true;
+            if y > 0 {
+                stack.push((y - 1, x));
+            }
+            if y + 1 < height {
+                stack.push((y + 1, x));
+            }
+            if x > 0 {
+                stack.push((y, x - 1));
+            }
+            if x + 1 < width {
+                stack.push((y, x + 1));
+            }
+        }
+
+        let on_loop = polygon.iter().collect::<HashSet<&Pos>>();
+        let mut res = HashSet::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let pos = Pos::new(y, x);
+                if !on_loop.contains(&pos) && !outside[3 * y + 1][3 * x + 1] {
+                    res.insert(pos);
+                }
+            }
+        }
+        res
+    }
+
+    /// Tiles enclosed by the main loop. The flood fill result is checked
+    /// against the count given by Pick's theorem.
+    #[allow(dead_code)]
+    pub fn enclosed_tiles(&self) -> HashSet<Pos> {
+        let polygon = self.main_loop();
+        let res = self.enclosed_tiles_flood_fill(&polygon);
+        let pick_count = PipeMaze::enclosed_count_pick(&polygon);
+        assert_eq!(
+            res.len(),
+            pick_count,
+            "Flood fill and Pick's theorem disagree on the enclosed area"
+        );
+        res
+    }
+
+    /// The kind of every tile of the map, relative to the main loop.
+    #[allow(dead_code)]
+    pub fn classify_tiles(&self) -> Vec<Vec<Tile>> {
+        let on_loop = self.main_loop().into_iter().collect::<HashSet<Pos>>();
+        let enclosed = self.enclosed_tiles();
+        (0..self.height())
+            .map(|y| {
+                (0..self.map[y].len())
+                    .map(|x| {
+                        let pos = Pos::new(y, x);
+                        if on_loop.contains(&pos) {
+                            Tile::Loop
+                        } else if enclosed.contains(&pos) {
+                            Tile::Enclosed
+                        } else {
+                            Tile::Outside
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Draw the map with box-drawing characters and ANSI colors. The main
+    /// loop is bold, other pipes are dimmed, and enclosed tiles are shaded.
+    #[allow(dead_code)]
+    pub fn render_ansi(&self) -> String {
+        let mut res = String::new();
+        for (y, line) in self.classify_tiles().iter().enumerate() {
+            let mut current_style = None;
+            for (x, tile) in line.iter().enumerate() {
+                let style = match tile {
+                    Tile::Loop => "1;33",
+                    Tile::Enclosed => "2;44",
+                    Tile::Outside => "2",
+                };
+                if current_style != Some(style) {
+                    res.push_str(&format!("\x1b[0;{}m", style));
+                    current_style = Some(style);
+                }
+                res.push(box_char(self.map[y][x]));
+            }
+            res.push_str("\x1b[0m\n");
+        }
+        res
+    }
+
+    /// Draw the map as a standalone HTML page, with the same styles as
+    /// `render_ansi`.
+    #[allow(dead_code)]
+    pub fn render_html(&self) -> String {
+        let mut res = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n\
+             body { background: #1e1e1e; color: #777; }\n\
+             pre { font-family: monospace; line-height: 1; }\n\
+             .loop { color: #f5c542; font-weight: bold; }\n\
+             .enclosed { background: #234a7a; }\n\
+             .outside { color: #555; }\n\
+             </style>\n</head>\n<body>\n<pre>\n",
+        );
+        for (y, line) in self.classify_tiles().iter().enumerate() {
+            for (x, tile) in line.iter().enumerate() {
+                let class = match tile {
+                    Tile::Loop => "loop",
+                    Tile::Enclosed => "enclosed",
+                    Tile::Outside => "outside",
+                };
+                res.push_str(&format!(
+                    "<span class=\"{}\">{}</span>",
+                    class,
+                    box_char(self.map[y][x])
+                ));
+            }
+            res.push('\n');
+        }
+        res.push_str("</pre>\n</body>\n</html>\n");
+        res
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Tile {
+    Loop,
+    Enclosed,
+    Outside,
+}
+
+/// The box-drawing character for a pipe. Ground is drawn as a middle dot.
+#[allow(dead_code)]
+pub fn box_char(pipe: char) -> char {
+    match pipe {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        '.' => '·',
+        _ => pipe,
+    }
+}
@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+mod maze;
+
+use maze::{read_map, PipeMaze};
 
 const FILENAME: &'static str = "day10/part1.in";
 
@@ -6,165 +8,18 @@ fn read_file() -> String {
     std::fs::read_to_string(FILENAME).expect("Something went wrong reading the file")
 }
 
-type Map = Vec<Vec<char>>;
-
-fn read_map(map: &str) -> Map {
-    map.lines().map(|line| line.chars().collect()).collect()
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Pos {
-    y: usize,
-    x: usize,
-}
-
-impl Pos {
-    fn new(y: usize, x: usize) -> Self {
-        Self { y, x }
-    }
-
-    fn get_pipe(&self, map: &Map) -> char {
-        map[self.y][self.x]
-    }
-
-    fn change_inside_outside(&self, map: &Map) -> bool {
-        let neighbors = self.neighbors(map);
-        if self.y == 0 {
-            return false;
-        }
-        let top_pipe = Pos::new(self.y - 1, self.x);
-        neighbors.contains(&top_pipe)
-    }
-
-    fn neighbors(&self, map: &Map) -> [Pos; 2] {
-        match self.get_pipe(map) {
-            '|' => [Pos::new(self.y - 1, self.x), Pos::new(self.y + 1, self.x)],
-            '-' => [Pos::new(self.y, self.x - 1), Pos::new(self.y, self.x + 1)],
-            'L' => [Pos::new(self.y - 1, self.x), Pos::new(self.y, self.x + 1)],
-            'J' => [Pos::new(self.y - 1, self.x), Pos::new(self.y, self.x - 1)],
-            '7' => [Pos::new(self.y + 1, self.x), Pos::new(self.y, self.x - 1)],
-            'F' => [Pos::new(self.y + 1, self.x), Pos::new(self.y, self.x + 1)],
-            _ => panic!("Invalid pipe '{}'", self.get_pipe(map)),
-        }
-    }
-
-    fn starting_pos_neighbors(&self, map: &Map) -> [Pos; 2] {
-        let mut neighbors = Vec::new();
-        if self.y != 0 {
-            neighbors.push(Pos::new(self.y - 1, self.x));
-        }
-        if self.y != map.len() - 1 {
-            neighbors.push(Pos::new(self.y + 1, self.x));
-        }
-        if self.x != 0 {
-            neighbors.push(Pos::new(self.y, self.x - 1));
-        }
-        if self.x != map[0].len() - 1 {
-            neighbors.push(Pos::new(self.y, self.x + 1));
-        }
-        let mut res = Vec::new();
-        for neighbor in neighbors {
-            if neighbor.get_pipe(map) == '.' {
-                continue;
-            }
-            if neighbor.neighbors(map).contains(self) {
-                res.push(neighbor);
-            }
-        }
-        assert!(res.len() == 2);
-        [res[0], res[1]]
-    }
-}
-
-fn starting_position(map: &Map) -> Pos {
-    for (y, line) in map.iter().enumerate() {
-        for (x, c) in line.iter().enumerate() {
-            if *c == 'S' {
-                return Pos::new(y, x);
-            }
-        }
-    }
-    panic!("No starting position found");
-}
-
-fn replace_s(map: &mut Map, starting_pos: Pos) {
-    let neighbors = starting_pos.starting_pos_neighbors(&map);
-    let has_up = if starting_pos.y != 0 {
-        neighbors.contains(&Pos::new(starting_pos.y - 1, starting_pos.x))
-    } else {
-        false
-    };
-    let has_down = if starting_pos.y != map.len() - 1 {
-        neighbors.contains(&Pos::new(starting_pos.y + 1, starting_pos.x))
-    } else {
-        false
-    };
-    let has_left = if starting_pos.x != 0 {
-        neighbors.contains(&Pos::new(starting_pos.y, starting_pos.x - 1))
-    } else {
-        false
-    };
-    let has_right = if starting_pos.x != map[0].len() - 1 {
-        neighbors.contains(&Pos::new(starting_pos.y, starting_pos.x + 1))
-    } else {
-        false
-    };
-    if has_up && has_down {
-        map[starting_pos.y][starting_pos.x] = '|';
-    } else if has_left && has_right {
-        map[starting_pos.y][starting_pos.x] = '-';
-    } else if has_up && has_right {
-        map[starting_pos.y][starting_pos.x] = 'L';
-    } else if has_up && has_left {
-        map[starting_pos.y][starting_pos.x] = 'J';
-    } else if has_down && has_left {
-        map[starting_pos.y][starting_pos.x] = '7';
-    } else if has_down && has_right {
-        map[starting_pos.y][starting_pos.x] = 'F';
-    } else {
-        panic!("Invalid starting position");
-    }
-}
-
 fn main() {
-    let mut map = read_map(&read_file());
-    let starting_pos = starting_position(&map);
-    let [neigh1, neigh2] = starting_pos.starting_pos_neighbors(&map);
-    replace_s(&mut map, starting_pos);
-
-    let mut cycle = HashSet::new();
-    cycle.insert(starting_pos);
-    let mut last = starting_pos;
-    let mut current = neigh1;
-    while current != neigh2 {
-        cycle.insert(current);
-        let [current_neigh1, current_neigh2] = current.neighbors(&map);
-        if current_neigh1 == last {
-            last = current;
-            current = current_neigh2;
-        } else {
-            last = current;
-            current = current_neigh1;
-        }
+    let maze = PipeMaze::new(read_map(&read_file()));
+    if std::env::args().any(|arg| arg == "--loops") {
+        maze.print_loops();
     }
-    cycle.insert(neigh2);
-
-    let mut num_inside = 0;
-    for i in 0..map.len() {
-        let mut inside = false;
-        for j in 0..map[i].len() {
-            let pos = Pos::new(i, j);
-            if (pos.get_pipe(&map) == '.') || (!cycle.contains(&pos)) {
-                if inside {
-                    num_inside += 1;
-                }
-                continue;
-            }
-            if pos.change_inside_outside(&map) {
-                inside = !inside;
-            }
-        }
-        assert!(!inside);
+    if std::env::args().any(|arg| arg == "--render") {
+        print!("{}", maze.render_ansi());
+    }
+    let args = std::env::args().collect::<Vec<String>>();
+    if let Some(idx) = args.iter().position(|arg| arg == "--html") {
+        let path = args.get(idx + 1).expect("--html expects a file name");
+        std::fs::write(path, maze.render_html()).expect("Something went wrong writing the file");
     }
-    println!("{}", num_inside);
+    println!("{}", maze.enclosed_tiles().len());
 }
@@ -0,0 +1,284 @@
+use std::collections::BinaryHeap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub y: i128,
+    pub x: i128,
+}
+
+pub fn read_map(map: &str) -> Vec<Pos> {
+    map.lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| Pos {
+                    y: y as i128,
+                    x: x as i128,
+                })
+        })
+        .collect()
+}
+
+/// Expand one axis: every empty line between the galaxies becomes `factor`
+/// lines. Each coordinate moves by `factor - 1` times the number of empty
+/// lines before it, which is found from the sorted distinct coordinates.
+fn expand_axis(coordinates: &[i128], factor: i128) -> Vec<i128> {
+    let mut sorted = coordinates.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    coordinates
+        .iter()
+        .map(|c| {
+            let rank = sorted.binary_search(c).unwrap() as i128;
+            let empty_before = c - sorted[0] - rank;
+            c + empty_before * (factor - 1)
+        })
+        .collect()
+}
+
+/// Positions of the galaxies once every empty row and column between them
+/// has been replaced by `factor` empty rows or columns.
+pub fn expand(galaxies: &[Pos], factor: i128) -> Vec<Pos> {
+    let ys = expand_axis(
+        &galaxies.iter().map(|pos| pos.y).collect::<Vec<_>>(),
+        factor,
+    );
+    let xs = expand_axis(
+        &galaxies.iter().map(|pos| pos.x).collect::<Vec<_>>(),
+        factor,
+    );
+    ys.into_iter().zip(xs).map(|(y, x)| Pos { y, x }).collect()
+}
+
+/// Sum of `|a - b|` over all unordered pairs. Once sorted, each value is
+/// greater than or equal to all values before it, so it contributes
+/// `value * i - (sum of the i values before it)`.
+fn sum_pairwise_differences(values: &mut [i128]) -> i128 {
+    values.sort_unstable();
+    let mut prefix = 0;
+    let mut res = 0;
+    for (i, value) in values.iter().enumerate() {
+        res += value * i as i128 - prefix;
+        prefix += value;
+    }
+    res
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    SquaredEuclidean,
+}
+
+impl Metric {
+    pub fn distance(self, a: Pos, b: Pos) -> i128 {
+        let dy = (a.y - b.y).abs();
+        let dx = (a.x - b.x).abs();
+        match self {
+            Metric::Manhattan => dy + dx,
+            Metric::Chebyshev => std::cmp::max(dy, dx),
+            Metric::SquaredEuclidean => dy * dy + dx * dx,
+        }
+    }
+
+    fn parse(s: &str) -> Metric {
+        match s {
+            "manhattan" => Metric::Manhattan,
+            "chebyshev" => Metric::Chebyshev,
+            "euclidean2" => Metric::SquaredEuclidean,
+            _ => panic!(
+                "Unknown metric {}, expected manhattan, chebyshev or euclidean2",
+                s
+            ),
+        }
+    }
+}
+
+fn sum_of_squared_differences(values: &[i128]) -> i128 {
+    // Sum over pairs of (a - b)^2 = n * sum(a^2) - (sum(a))^2
+    let n = values.len() as i128;
+    let sum = values.iter().sum::<i128>();
+    let sum_of_squares = values.iter().map(|x| x * x).sum::<i128>();
+    n * sum_of_squares - sum * sum
+}
+
+/// Sum of the distances over all unordered pairs of galaxies, without
+/// enumerating the pairs. Chebyshev distances are Manhattan distances of
+/// the map rotated by 45 degrees, halved.
+pub fn sum_pairwise_distances(galaxies: &[Pos], metric: Metric) -> i128 {
+    let axis = |f: &dyn Fn(&Pos) -> i128| galaxies.iter().map(f).collect::<Vec<i128>>();
+    match metric {
+        Metric::Manhattan => {
+            sum_pairwise_differences(&mut axis(&|pos| pos.y))
+                + sum_pairwise_differences(&mut axis(&|pos| pos.x))
+        }
+        Metric::Chebyshev => {
+            (sum_pairwise_differences(&mut axis(&|pos| pos.y + pos.x))
+                + sum_pairwise_differences(&mut axis(&|pos| pos.y - pos.x)))
+                / 2
+        }
+        Metric::SquaredEuclidean => {
+            sum_of_squared_differences(&axis(&|pos| pos.y))
+                + sum_of_squared_differences(&axis(&|pos| pos.x))
+        }
+    }
+}
+
+/// A pair of galaxies, given by their indices, and their distance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pair {
+    pub distance: i128,
+    pub first: usize,
+    pub second: usize,
+}
+
+fn all_pairs(galaxies: &[Pos], metric: Metric) -> impl Iterator<Item = Pair> + '_ {
+    (0..galaxies.len()).flat_map(move |first| {
+        ((first + 1)..galaxies.len()).map(move |second| Pair {
+            distance: metric.distance(galaxies[first], galaxies[second]),
+            first,
+            second,
+        })
+    })
+}
+
+/// The `k` closest pairs of galaxies, from the closest.
+pub fn closest_pairs(galaxies: &[Pos], metric: Metric, k: usize) -> Vec<Pair> {
+    // Max-heap of the k closest pairs seen so far.
+    let mut heap = BinaryHeap::new();
+    for pair in all_pairs(galaxies, metric) {
+        heap.push(pair);
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+}
+
+/// The `k` farthest pairs of galaxies, from the farthest.
+pub fn farthest_pairs(galaxies: &[Pos], metric: Metric, k: usize) -> Vec<Pair> {
+    // Min-heap of the k farthest pairs seen so far.
+    let mut heap = BinaryHeap::new();
+    for pair in all_pairs(galaxies, metric) {
+        heap.push(std::cmp::Reverse(pair));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|x| x.0).collect()
+}
+
+/// For each galaxy, the index of its nearest galaxy and their distance.
+pub fn nearest_neighbors(galaxies: &[Pos], metric: Metric) -> Vec<Option<(usize, i128)>> {
+    let mut res: Vec<Option<(usize, i128)>> = vec![None; galaxies.len()];
+    for pair in all_pairs(galaxies, metric) {
+        for (galaxy, other) in [(pair.first, pair.second), (pair.second, pair.first)] {
+            if res[galaxy].is_none_or(|(_, distance)| pair.distance < distance) {
+                res[galaxy] = Some((other, pair.distance));
+            }
+        }
+    }
+    res
+}
+
+/// Number of pairs in each of `num_buckets` distance ranges of equal width,
+/// from the smallest to the largest distance. Each bucket is given as
+/// `(lowest distance, highest distance, number of pairs)`.
+pub fn distance_histogram(
+    galaxies: &[Pos],
+    metric: Metric,
+    num_buckets: usize,
+) -> Vec<(i128, i128, usize)> {
+    let distances = all_pairs(galaxies, metric)
+        .map(|pair| pair.distance)
+        .collect::<Vec<i128>>();
+    let (Some(&min), Some(&max)) = (distances.iter().min(), distances.iter().max()) else {
+        return Vec::new();
+    };
+    let num_buckets = num_buckets.max(1) as i128;
+    let width = (max - min) / num_buckets + 1;
+    let mut res = (0..num_buckets)
+        .map(|i| (min + i * width, min + (i + 1) * width - 1, 0))
+        .collect::<Vec<_>>();
+    for distance in distances {
+        res[((distance - min) / width) as usize].2 += 1;
+    }
+    res
+}
+
+/// Answer the queries given on the command line about the expanded
+/// galaxies, then print the sum of pairwise distances. The metric is chosen
+/// with `--metric`, and the queries are `--closest <k>`, `--farthest <k>`,
+/// `--nearest` and `--histogram <buckets>`.
+pub fn run(galaxies: &[Pos]) {
+    let args = std::env::args().collect::<Vec<String>>();
+    let value = |name: &str| {
+        let idx = args.iter().position(|arg| arg == name)?;
+        Some(
+            args.get(idx + 1)
+                .unwrap_or_else(|| panic!("{} expects a value", name))
+                .as_str(),
+        )
+    };
+    let count = |name: &str| {
+        value(name).map(|x| {
+            x.parse::<usize>()
+                .unwrap_or_else(|_| panic!("{} expects a number", name))
+        })
+    };
+    let metric = value("--metric").map_or(Metric::Manhattan, Metric::parse);
+    let print_pair = |pair: &Pair| {
+        let (a, b) = (galaxies[pair.first], galaxies[pair.second]);
+        println!(
+            "    ({}, {}) - ({}, {}): {}",
+            a.y, a.x, b.y, b.x, pair.distance
+        );
+    };
+
+    if let Some(k) = count("--closest") {
+        println!("{} closest pairs:", k);
+        closest_pairs(galaxies, metric, k)
+            .iter()
+            .for_each(print_pair);
+    }
+    if let Some(k) = count("--farthest") {
+        println!("{} farthest pairs:", k);
+        farthest_pairs(galaxies, metric, k)
+            .iter()
+            .for_each(print_pair);
+    }
+    if args.iter().any(|arg| arg == "--nearest") {
+        println!("Nearest neighbors:");
+        for (galaxy, nearest) in galaxies.iter().zip(nearest_neighbors(galaxies, metric)) {
+            if let Some((other, distance)) = nearest {
+                let other = galaxies[other];
+                println!(
+                    "    ({}, {}) -> ({}, {}): {}",
+                    galaxy.y, galaxy.x, other.y, other.x, distance
+                );
+            }
+        }
+    }
+    if let Some(num_buckets) = count("--histogram") {
+        println!("Pair distances:");
+        for (low, high, num_pairs) in distance_histogram(galaxies, metric, num_buckets) {
+            println!("    {}..={}: {}", low, high, num_pairs);
+        }
+    }
+    println!("{}", sum_pairwise_distances(galaxies, metric));
+}
+
+/// Parse the `--factor <n>` argument, or use the default expansion factor.
+pub fn read_factor(default: i128) -> i128 {
+    let args = std::env::args().collect::<Vec<String>>();
+    match args.iter().position(|arg| arg == "--factor") {
+        Some(idx) => args
+            .get(idx + 1)
+            .and_then(|x| x.parse::<i128>().ok())
+            .expect("--factor expects a number"),
+        None => default,
+    }
+}
@@ -0,0 +1,570 @@
+use num_bigint::BigUint;
+use num_traits::{CheckedAdd, One, Zero};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spring {
+    Operational,
+    Damaged,
+    Unknown,
+}
+
+/// A row of springs, with the sizes of the groups of damaged springs.
+#[derive(Clone, Debug)]
+pub struct Row {
+    pub springs: Vec<Spring>,
+    pub groups: Vec<usize>,
+}
+
+fn read_list_springs(springs: &str) -> Result<Vec<Spring>, String> {
+    springs
+        .chars()
+        .map(|c| match c {
+            '.' => Ok(Spring::Operational),
+            '#' => Ok(Spring::Damaged),
+            '?' => Ok(Spring::Unknown),
+            _ => Err(format!("Invalid spring {:?} in {:?}", c, springs)),
+        })
+        .collect()
+}
+
+fn read_problem(problem: &str) -> Row {
+    let springs = read_list_springs(problem.split_whitespace().next().unwrap())
+        .unwrap_or_else(|err| panic!("{}", err));
+    let groups = problem
+        .split_whitespace()
+        .nth(1)
+        .unwrap()
+        .split(',')
+        .map(|s| s.parse::<usize>().unwrap())
+        .collect();
+    Row { springs, groups }
+}
+
+pub fn read_problems(problems: &str) -> Vec<Row> {
+    problems.lines().map(read_problem).collect()
+}
+
+impl Row {
+    /// Repeat the springs `factor` times separated by unknown springs, and
+    /// the groups `factor` times.
+    pub fn unfold(&self, factor: usize) -> Row {
+        let mut springs = Vec::with_capacity((self.springs.len() + 1) * factor);
+        for i in 0..factor {
+            if i != 0 {
+                springs.push(Spring::Unknown);
+            }
+            springs.extend_from_slice(&self.springs);
+        }
+        Row {
+            springs,
+            groups: self.groups.repeat(factor),
+        }
+    }
+
+    /// Table of the number of arrangements of each suffix of the row, or
+    /// `None` if a count overflows `T`.
+    ///
+    /// `counts[i * (m + 1) + j]` is the number of arrangements of the
+    /// springs from index `i` with the groups from index `j`, and is filled
+    /// from the end of the row. A group of size `k` can start at `i` if the
+    /// `k` springs from `i` can all be damaged, and the spring after them
+    /// can be operational.
+    fn count_table<T: Clone + Zero + One + CheckedAdd>(&self) -> Option<Vec<T>> {
+        let n = self.springs.len();
+        let m = self.groups.len();
+        // operational_before[i] is the number of operational springs before i.
+        let mut operational_before = vec![0; n + 1];
+        for (i, spring) in self.springs.iter().enumerate() {
+            operational_before[i + 1] =
+                operational_before[i] + (*spring == Spring::Operational) as usize;
+        }
+        let can_be_group = |i: usize, k: usize| {
+            i + k <= n
+                && operational_before[i + k] == operational_before[i]
+                && (i + k == n || self.springs[i + k] != Spring::Damaged)
+        };
+
+        let mut counts = vec![T::zero(); (n + 1) * (m + 1)];
+        counts[n * (m + 1) + m] = T::one();
+        for i in (0..n).rev() {
+            for j in 0..=m {
+                let mut res = T::zero();
+                if self.springs[i] != Spring::Damaged {
+                    res = res.checked_add(&counts[(i + 1) * (m + 1) + j])?;
+                }
+                if self.springs[i] != Spring::Operational
+                    && j < m
+                    && can_be_group(i, self.groups[j])
+                {
+                    let next = std::cmp::min(i + self.groups[j] + 1, n);
+                    res = res.checked_add(&counts[next * (m + 1) + j + 1])?;
+                }
+                counts[i * (m + 1) + j] = res;
+            }
+        }
+        Some(counts)
+    }
+
+    /// Number of arrangements of the springs matching the groups, or `None`
+    /// if it overflows `T`.
+    pub fn count_arrangements<T: Clone + Zero + One + CheckedAdd>(&self) -> Option<T> {
+        Some(self.count_table::<T>()?.swap_remove(0))
+    }
+
+    /// Number of arrangements, using `u128` when it doesn't overflow.
+    pub fn count(&self) -> BigUint {
+        match self.count_arrangements::<u128>() {
+            Some(res) => BigUint::from(res),
+            None => self.count_arrangements::<BigUint>().unwrap(),
+        }
+    }
+}
+
+/// An arrangement of a row, where every spring is known.
+pub type Arrangement = Vec<Spring>;
+
+pub fn format_springs(springs: &[Spring]) -> String {
+    springs
+        .iter()
+        .map(|spring| match spring {
+            Spring::Operational => '.',
+            Spring::Damaged => '#',
+            Spring::Unknown => '?',
+        })
+        .collect()
+}
+
+/// Arrangements of a row, indexed in lexicographic order of their text,
+/// where `#` comes before `.`. The counts of the DP are used to skip whole
+/// subtrees of arrangements.
+pub struct Arrangements<'a> {
+    row: &'a Row,
+    counts: Vec<BigUint>,
+}
+
+impl<'a> Arrangements<'a> {
+    pub fn new(row: &'a Row) -> Arrangements<'a> {
+        Arrangements {
+            row,
+            counts: row.count_table::<BigUint>().unwrap(),
+        }
+    }
+
+    pub fn len(&self) -> BigUint {
+        self.counts[0].clone()
+    }
+
+    fn count_from(&self, i: usize, j: usize) -> &BigUint {
+        &self.counts[i * (self.row.groups.len() + 1) + j]
+    }
+
+    /// Whether a group of size `k` can start at `i`.
+    fn can_be_group(&self, i: usize, k: usize) -> bool {
+        let springs = &self.row.springs;
+        i + k <= springs.len()
+            && springs[i..i + k].iter().all(|s| *s != Spring::Operational)
+            && (i + k == springs.len() || springs[i + k] != Spring::Damaged)
+    }
+
+    /// The `k`-th arrangement, starting from 0, if there are more than `k`.
+    pub fn nth(&self, mut k: BigUint) -> Option<Arrangement> {
+        if k >= self.len() {
+            return None;
+        }
+        let n = self.row.springs.len();
+        let m = self.row.groups.len();
+        let mut res = Vec::with_capacity(n);
+        let (mut i, mut j) = (0, 0);
+        while i < n {
+            // Starting a group writes a `#`, so it comes first.
+            if self.row.springs[i] != Spring::Operational
+                && j < m
+                && self.can_be_group(i, self.row.groups[j])
+            {
+                let size = self.row.groups[j];
+                let next = std::cmp::min(i + size + 1, n);
+                let count = self.count_from(next, j + 1);
+                if k < *count {
+                    res.extend(std::iter::repeat_n(Spring::Damaged, size));
+                    if next > i + size {
+                        res.push(Spring::Operational);
+                    }
+                    i = next;
+                    j += 1;
+                    continue;
+                }
+                k -= count;
+            }
+            res.push(Spring::Operational);
+            i += 1;
+        }
+        Some(res)
+    }
+
+    /// An arrangement chosen uniformly at random.
+    pub fn sample(&self, rng: &mut Rng) -> Option<Arrangement> {
+        let len = self.len();
+        if len.is_zero() {
+            return None;
+        }
+        self.nth(rng.below(&len))
+    }
+
+    /// Iterate lazily over the arrangements, in lexicographic order.
+    pub fn iter(&self) -> impl Iterator<Item = Arrangement> + '_ {
+        let mut k = BigUint::zero();
+        std::iter::from_fn(move || {
+            let res = self.nth(k.clone());
+            k += 1u32;
+            res
+        })
+    }
+}
+
+/// A small xorshift random number generator, so that samples can be
+/// reproduced from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A uniform number in `0..bound`, by rejection sampling on the number
+    /// of bits of `bound`.
+    pub fn below(&mut self, bound: &BigUint) -> BigUint {
+        let bits = bound.bits();
+        loop {
+            let digits = (0..bits.div_ceil(64))
+                .map(|_| self.next_u64())
+                .collect::<Vec<u64>>();
+            let mut res = BigUint::from_slice(
+                &digits
+                    .iter()
+                    .flat_map(|x| [*x as u32, (*x >> 32) as u32])
+                    .collect::<Vec<u32>>(),
+            );
+            res &= (BigUint::one() << bits) - 1u32;
+            if res < *bound {
+                return res;
+            }
+        }
+    }
+}
+
+/// The first constraint broken by a partial assignment of a row.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation {
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    ConflictsWithRow {
+        index: usize,
+    },
+    TooManyDamaged {
+        expected: usize,
+        found: usize,
+    },
+    TooFewDamaged {
+        expected: usize,
+        available: usize,
+    },
+    GroupTooLong {
+        index: usize,
+        size: usize,
+        max: usize,
+    },
+    TooManyGroups {
+        index: usize,
+    },
+    WrongGroupSize {
+        group: usize,
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    NoArrangement,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::WrongLength { expected, found } => {
+                write!(f, "has {} springs instead of {}", found, expected)
+            }
+            Violation::ConflictsWithRow { index } => {
+                write!(f, "spring {} contradicts the known spring", index)
+            }
+            Violation::TooManyDamaged { expected, found } => {
+                write!(f, "has {} damaged springs, but the groups only have {}", found, expected)
+            }
+            Violation::TooFewDamaged { expected, available } => write!(
+                f,
+                "can have at most {} damaged springs, but the groups need {}",
+                available, expected
+            ),
+            Violation::GroupTooLong { index, size, max } => write!(
+                f,
+                "the group at spring {} has at least {} damaged springs, but the largest group has {}",
+                index, size, max
+            ),
+            Violation::TooManyGroups { index } => {
+                write!(f, "the group at spring {} is one group too many", index)
+            }
+            Violation::WrongGroupSize {
+                group,
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "group {} at spring {} has {} damaged springs instead of {}",
+                group, index, found, expected
+            ),
+            Violation::NoArrangement => write!(f, "can't be completed into a valid arrangement"),
+        }
+    }
+}
+
+impl Row {
+    /// Explain why a partial assignment of the row, where some unknown
+    /// springs have been chosen, has no valid completion. Return `None` if
+    /// it can be completed.
+    pub fn explain(&self, assignment: &[Spring]) -> Option<Violation> {
+        if assignment.len() != self.springs.len() {
+            return Some(Violation::WrongLength {
+                expected: self.springs.len(),
+                found: assignment.len(),
+            });
+        }
+        if let Some(index) = (0..assignment.len())
+            .find(|&i| self.springs[i] != Spring::Unknown && assignment[i] != self.springs[i])
+        {
+            return Some(Violation::ConflictsWithRow { index });
+        }
+
+        let expected = self.groups.iter().sum::<usize>();
+        let found = assignment.iter().filter(|s| **s == Spring::Damaged).count();
+        let available = assignment
+            .iter()
+            .filter(|s| **s != Spring::Operational)
+            .count();
+        if found > expected {
+            return Some(Violation::TooManyDamaged { expected, found });
+        }
+        if available < expected {
+            return Some(Violation::TooFewDamaged {
+                expected,
+                available,
+            });
+        }
+
+        // Runs of damaged springs, with whether they are closed on both sides
+        // by operational springs or the ends of the row.
+        let mut runs = Vec::new();
+        let mut i = 0;
+        while i < assignment.len() {
+            if assignment[i] != Spring::Damaged {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < assignment.len() && assignment[i] == Spring::Damaged {
+                i += 1;
+            }
+            let closed = (start == 0 || assignment[start - 1] == Spring::Operational)
+                && (i == assignment.len() || assignment[i] == Spring::Operational);
+            runs.push((start, i - start, closed));
+        }
+
+        let max = self.groups.iter().copied().max().unwrap_or(0);
+        if let Some(&(index, size, _)) = runs.iter().find(|(_, size, _)| *size > max) {
+            return Some(Violation::GroupTooLong { index, size, max });
+        }
+
+        // While no unknown spring comes before a run, it is exactly the next
+        // group.
+        for (group, &(index, size, closed)) in runs.iter().enumerate() {
+            if assignment[..index].contains(&Spring::Unknown) || !closed {
+                break;
+            }
+            match self.groups.get(group) {
+                None => return Some(Violation::TooManyGroups { index }),
+                Some(&expected) if expected != size => {
+                    return Some(Violation::WrongGroupSize {
+                        group,
+                        index,
+                        expected,
+                        found: size,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        let partial = Row {
+            springs: assignment.to_vec(),
+            groups: self.groups.clone(),
+        };
+        if partial.count().is_zero() {
+            return Some(Violation::NoArrangement);
+        }
+        None
+    }
+
+    /// Number of arrangements, by trying every assignment of the unknown
+    /// springs. This is only usable on rows with few unknown springs.
+    pub fn count_brute_force(&self) -> usize {
+        let unknowns = (0..self.springs.len())
+            .filter(|&i| self.springs[i] == Spring::Unknown)
+            .collect::<Vec<usize>>();
+        let mut springs = self.springs.clone();
+        let mut res = 0;
+        for mask in 0..(1usize << unknowns.len()) {
+            for (bit, &i) in unknowns.iter().enumerate() {
+                springs[i] = if mask & (1 << bit) != 0 {
+                    Spring::Damaged
+                } else {
+                    Spring::Operational
+                };
+            }
+            let groups = springs
+                .split(|s| *s == Spring::Operational)
+                .map(|run| run.len())
+                .filter(|&len| len > 0)
+                .collect::<Vec<usize>>();
+            res += (groups == self.groups) as usize;
+        }
+        res
+    }
+}
+
+/// Handle the tools given on the command line, on the row selected with
+/// `--row <index>`: `--list <n>` prints the first arrangements, `--kth <k>`
+/// the k-th one, `--sample` a random one (seeded with `--seed`), and
+/// `--explain <assignment>` why a partial assignment fails. `--verify`
+/// checks the DP against brute force on every row with few unknowns.
+/// Return whether a tool was used.
+pub fn run_tools(rows: &[Row]) -> bool {
+    let args = std::env::args().collect::<Vec<String>>();
+    let has_flag = |name: &str| args.iter().any(|arg| arg == name);
+    let value = |name: &str| {
+        let idx = args.iter().position(|arg| arg == name)?;
+        Some(
+            args.get(idx + 1)
+                .unwrap_or_else(|| panic!("{} expects a value", name))
+                .as_str(),
+        )
+    };
+
+    if has_flag("--verify") {
+        let mut num_checked = 0;
+        for (idx, row) in rows.iter().enumerate() {
+            let num_unknown = row
+                .springs
+                .iter()
+                .filter(|s| **s == Spring::Unknown)
+                .count();
+            if num_unknown > 20 {
+                continue;
+            }
+            let expected = BigUint::from(row.count_brute_force());
+            assert_eq!(row.count(), expected, "Wrong count on row {}", idx);
+            num_checked += 1;
+        }
+        println!("{} rows checked against brute force", num_checked);
+        return true;
+    }
+
+    let Some(row) = value("--row") else {
+        return false;
+    };
+    let idx = row.parse::<usize>().expect("--row expects an index");
+    let Some(row) = rows.get(idx) else {
+        eprintln!("No row {}, there are {} rows", idx, rows.len());
+        return true;
+    };
+    let arrangements = Arrangements::new(row);
+    println!(
+        "{} {:?}: {} arrangements",
+        format_springs(&row.springs),
+        row.groups,
+        arrangements.len()
+    );
+    if let Some(n) = value("--list") {
+        let n = n.parse::<usize>().expect("--list expects a number");
+        for arrangement in arrangements.iter().take(n) {
+            println!("{}", format_springs(&arrangement));
+        }
+    }
+    if let Some(k) = value("--kth") {
+        let k = k.parse::<BigUint>().expect("--kth expects a number");
+        match arrangements.nth(k) {
+            Some(arrangement) => println!("{}", format_springs(&arrangement)),
+            None => println!("There are not that many arrangements"),
+        }
+    }
+    if has_flag("--sample") {
+        let seed = value("--seed").map_or_else(
+            || {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos() as u64
+            },
+            |x| x.parse::<u64>().expect("--seed expects a number"),
+        );
+        match arrangements.sample(&mut Rng::new(seed)) {
+            Some(arrangement) => println!("{}", format_springs(&arrangement)),
+            None => println!("No arrangement"),
+        }
+    }
+    if let Some(assignment) = value("--explain") {
+        match read_list_springs(assignment).map(|springs| row.explain(&springs)) {
+            Ok(Some(violation)) => println!("{}: {}", assignment, violation),
+            Ok(None) => println!("{}: can be completed", assignment),
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+    true
+}
+
+/// Total number of arrangements of the rows. With `parallel`, the rows are
+/// split between the available threads.
+pub fn count_all(rows: &[Row], parallel: bool) -> BigUint {
+    if !parallel || rows.is_empty() {
+        return rows.iter().map(|row| row.count()).sum();
+    }
+    let num_threads = std::thread::available_parallelism().map_or(1, |x| x.get());
+    let chunk_size = rows.len().div_ceil(num_threads);
+    std::thread::scope(|scope| {
+        let handles = rows
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|row| row.count()).sum::<BigUint>()))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    })
+}
+
+/// Parse the `--unfold <factor>` argument, or use the default factor.
+pub fn read_unfold_factor(default: usize) -> usize {
+    let args = std::env::args().collect::<Vec<String>>();
+    match args.iter().position(|arg| arg == "--unfold") {
+        Some(idx) => args
+            .get(idx + 1)
+            .and_then(|x| x.parse::<usize>().ok())
+            .expect("--unfold expects a number"),
+        None => default,
+    }
+}
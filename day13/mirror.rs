@@ -0,0 +1,407 @@
+/// A row or column of a pattern, as a bitset where bit `i` is its `i`-th
+/// cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line(Vec<u64>);
+
+/// The `len` lowest bits.
+fn mask(len: usize) -> u64 {
+    u64::MAX.checked_shr(64 - len as u32).unwrap_or(0)
+}
+
+impl Line {
+    fn new(len: usize) -> Line {
+        Line(vec![0; len.div_ceil(64)])
+    }
+
+    fn set(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Number of cells differing from a line of the same length.
+    fn differences(&self, other: &Line) -> u32 {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(x, y)| (x ^ y).count_ones())
+            .sum()
+    }
+
+    /// The cells `start..start + len`, as a line of their own.
+    fn window(&self, start: usize, len: usize) -> Line {
+        let (word, shift) = (start / 64, start % 64);
+        let mut res = Line(
+            (0..len.div_ceil(64))
+                .map(|k| {
+                    let low = self.0.get(word + k).copied().unwrap_or(0) >> shift;
+                    let high = self.0.get(word + k + 1).copied().unwrap_or(0);
+                    low | high.checked_shl(64 - shift as u32).unwrap_or(0)
+                })
+                .collect(),
+        );
+        if let Some(last) = res.0.last_mut() {
+            *last &= mask(len - (len - 1) / 64 * 64);
+        }
+        res
+    }
+
+    /// The first `len` cells, in reverse order.
+    fn reversed(&self, len: usize) -> Line {
+        let reversed = Line(self.0.iter().rev().map(|x| x.reverse_bits()).collect());
+        reversed.window(self.0.len() * 64 - len, len)
+    }
+}
+
+/// A pattern of ash (`.`) and rocks (`#`), stored as one bitset per row and
+/// one per column.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    pub rows: Vec<Line>,
+    pub columns: Vec<Line>,
+}
+
+impl Pattern {
+    pub fn new(cells: &[Vec<bool>]) -> Pattern {
+        let height = cells.len();
+        let width = cells.first().map_or(0, |row| row.len());
+        assert!(
+            cells.iter().all(|row| row.len() == width),
+            "Rows of a pattern should have the same length"
+        );
+        let mut rows = vec![Line::new(width); height];
+        let mut columns = vec![Line::new(height); width];
+        for (y, row) in cells.iter().enumerate() {
+            for (x, &rock) in row.iter().enumerate() {
+                if rock {
+                    rows[y].set(x);
+                    columns[x].set(y);
+                }
+            }
+        }
+        Pattern {
+            width,
+            height,
+            rows,
+            columns,
+        }
+    }
+}
+
+fn read_one_problem(input: &str) -> Pattern {
+    let cells = input
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => true,
+                    '.' => false,
+                    _ => panic!("Invalid input"),
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<bool>>>();
+    Pattern::new(&cells)
+}
+
+pub fn read_problems(input: &str) -> Vec<Pattern> {
+    input.split("\n\n").map(read_one_problem).collect()
+}
+
+/// The smudge budget, from `--smudges <k>`.
+pub fn read_smudges(default: u32) -> u32 {
+    let args = std::env::args().collect::<Vec<String>>();
+    match args.iter().position(|arg| arg == "--smudges") {
+        Some(idx) => args
+            .get(idx + 1)
+            .and_then(|k| k.parse::<u32>().ok())
+            .expect("--smudges expects a number"),
+        None => default,
+    }
+}
+
+/// A reflection line, given by the number of columns on its left or the
+/// number of rows above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Vertical(usize),
+    Horizontal(usize),
+}
+
+impl Axis {
+    pub fn score(&self) -> usize {
+        match self {
+            Axis::Vertical(columns) => *columns,
+            Axis::Horizontal(rows) => 100 * rows,
+        }
+    }
+}
+
+/// A symmetry of a pattern. Mirrors centred on a line are given by the
+/// index of that column or row. Diagonal symmetries hold on a square window
+/// of the pattern, given by its top left corner and size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Mirror(Axis),
+    CentredMirror(Axis),
+    Rotation,
+    Diagonal { y: usize, x: usize, size: usize },
+    AntiDiagonal { y: usize, x: usize, size: usize },
+}
+
+impl Symmetry {
+    /// Mirrors score as in the puzzle, and centred mirrors as a mirror line
+    /// just before their centre. Other symmetries score the number of cells
+    /// they cover.
+    pub fn score(&self, pattern: &Pattern) -> usize {
+        match self {
+            Symmetry::Mirror(axis) | Symmetry::CentredMirror(axis) => axis.score(),
+            Symmetry::Rotation => pattern.width * pattern.height,
+            Symmetry::Diagonal { size, .. } | Symmetry::AntiDiagonal { size, .. } => size * size,
+        }
+    }
+}
+
+/// The kinds of symmetry to look for, from `--kinds <kind,...>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Mirror,
+    CentredMirror,
+    Rotation,
+    Diagonal,
+    AntiDiagonal,
+}
+
+pub fn read_kinds() -> Vec<Kind> {
+    let args = std::env::args().collect::<Vec<String>>();
+    let Some(idx) = args.iter().position(|arg| arg == "--kinds") else {
+        return vec![Kind::Mirror];
+    };
+    args.get(idx + 1)
+        .expect("--kinds expects a list of kinds")
+        .split(',')
+        .map(|kind| match kind {
+            "mirror" => Kind::Mirror,
+            "centred" => Kind::CentredMirror,
+            "rotation" => Kind::Rotation,
+            "diagonal" => Kind::Diagonal,
+            "anti-diagonal" => Kind::AntiDiagonal,
+            _ => panic!(
+                "Unknown kind {:?}, expected mirror, centred, rotation, diagonal or anti-diagonal",
+                kind
+            ),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detection {
+    pub symmetry: Symmetry,
+    /// Number of cells that differ from their image.
+    pub differences: u32,
+}
+
+/// Number of differing cells between the lines before `before_end` and
+/// the lines from `after_start`, paired from the inside out, or `None` as
+/// soon as there are more than `max_differences`.
+fn differences(
+    lines: &[Line],
+    before_end: usize,
+    after_start: usize,
+    max_differences: u32,
+) -> Option<u32> {
+    let mut res = 0;
+    for (before, after) in lines[..before_end].iter().rev().zip(&lines[after_start..]) {
+        res += before.differences(after);
+        if res > max_differences {
+            return None;
+        }
+    }
+    Some(res)
+}
+
+/// Mirrors between two lines, given by the number of lines before them.
+fn mirrors_of(lines: &[Line], max_differences: u32) -> Vec<(usize, u32)> {
+    (1..lines.len())
+        .filter_map(|split| Some((split, differences(lines, split, split, max_differences)?)))
+        .collect()
+}
+
+/// Mirrors centred on a line, which is its own image.
+fn centred_mirrors_of(lines: &[Line], max_differences: u32) -> Vec<(usize, u32)> {
+    (1..lines.len().saturating_sub(1))
+        .filter_map(|centre| {
+            Some((
+                centre,
+                differences(lines, centre, centre + 1, max_differences)?,
+            ))
+        })
+        .collect()
+}
+
+impl Pattern {
+    /// Number of cells differing from their image by a rotation of 180°.
+    fn rotation_differences(&self) -> u32 {
+        // Both cells of a differing pair are counted.
+        (0..self.height)
+            .map(|y| self.rows[y].differences(&self.rows[self.height - 1 - y].reversed(self.width)))
+            .sum::<u32>()
+            / 2
+    }
+
+    /// Number of cells of the square window differing from their image by
+    /// a reflection along its diagonal, or its anti-diagonal.
+    fn diagonal_differences(&self, y: usize, x: usize, size: usize, anti: bool) -> u32 {
+        // Row `i` of the window is the image of its column `i` on the
+        // diagonal, and of its reversed column `size - 1 - i` on the
+        // anti-diagonal.
+        (0..size)
+            .map(|i| {
+                let row = self.rows[y + i].window(x, size);
+                let column = if anti {
+                    self.columns[x + size - 1 - i]
+                        .window(y, size)
+                        .reversed(size)
+                } else {
+                    self.columns[x + i].window(y, size)
+                };
+                row.differences(&column)
+            })
+            .sum::<u32>()
+            / 2
+    }
+
+    /// The largest square window, of at least 2 cells per side, whose
+    /// number of differences is accepted, in reading order among windows
+    /// of the same size.
+    fn largest_diagonal(
+        &self,
+        anti: bool,
+        accept: &impl Fn(u32) -> bool,
+    ) -> Option<(usize, usize, usize, u32)> {
+        for size in (2..=std::cmp::min(self.width, self.height)).rev() {
+            for y in 0..=self.height - size {
+                for x in 0..=self.width - size {
+                    let differences = self.diagonal_differences(y, x, size, anti);
+                    if accept(differences) {
+                        return Some((y, x, size, differences));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Symmetries of the given kinds whose number of differences is
+    /// accepted, with at most `max_differences` differences. Diagonal
+    /// symmetries only report their largest window.
+    fn detect(
+        &self,
+        kinds: &[Kind],
+        max_differences: u32,
+        accept: impl Fn(u32) -> bool,
+    ) -> Vec<Detection> {
+        let mut res = Vec::new();
+        for kind in kinds {
+            match kind {
+                Kind::Mirror | Kind::CentredMirror => {
+                    let find = if *kind == Kind::Mirror {
+                        mirrors_of
+                    } else {
+                        centred_mirrors_of
+                    };
+                    let make = |axis| {
+                        if *kind == Kind::Mirror {
+                            Symmetry::Mirror(axis)
+                        } else {
+                            Symmetry::CentredMirror(axis)
+                        }
+                    };
+                    for (split, differences) in find(&self.columns, max_differences) {
+                        res.push(Detection {
+                            symmetry: make(Axis::Vertical(split)),
+                            differences,
+                        });
+                    }
+                    for (split, differences) in find(&self.rows, max_differences) {
+                        res.push(Detection {
+                            symmetry: make(Axis::Horizontal(split)),
+                            differences,
+                        });
+                    }
+                }
+                Kind::Rotation => res.push(Detection {
+                    symmetry: Symmetry::Rotation,
+                    differences: self.rotation_differences(),
+                }),
+                Kind::Diagonal | Kind::AntiDiagonal => {
+                    let anti = *kind == Kind::AntiDiagonal;
+                    if let Some((y, x, size, differences)) =
+                        self.largest_diagonal(anti, &|d| d <= max_differences && accept(d))
+                    {
+                        let symmetry = if anti {
+                            Symmetry::AntiDiagonal { y, x, size }
+                        } else {
+                            Symmetry::Diagonal { y, x, size }
+                        };
+                        res.push(Detection {
+                            symmetry,
+                            differences,
+                        });
+                    }
+                }
+            }
+        }
+        res.retain(|detection| {
+            detection.differences <= max_differences && accept(detection.differences)
+        });
+        res
+    }
+
+    /// Every symmetry of the given kinds with at most `max_differences`
+    /// differing cells.
+    pub fn symmetries(&self, kinds: &[Kind], max_differences: u32) -> Vec<Detection> {
+        self.detect(kinds, max_differences, |_| true)
+    }
+
+    /// The symmetries that hold once exactly `smudges` cells are fixed.
+    pub fn mirrors(&self, kinds: &[Kind], smudges: u32) -> Vec<Symmetry> {
+        self.detect(kinds, smudges, |differences| differences == smudges)
+            .into_iter()
+            .map(|detection| detection.symmetry)
+            .collect()
+    }
+}
+
+/// Sum of the scores of the symmetries of the given kinds of every pattern,
+/// with `smudges` fixed cells. With `--all`, print every symmetry within
+/// the budget.
+pub fn summarize(patterns: &[Pattern], kinds: &[Kind], smudges: u32) -> usize {
+    if std::env::args().any(|arg| arg == "--all") {
+        for (idx, pattern) in patterns.iter().enumerate() {
+            for detection in pattern.symmetries(kinds, smudges) {
+                println!(
+                    "pattern {}: {:?} with {} differences, score {}",
+                    idx,
+                    detection.symmetry,
+                    detection.differences,
+                    detection.symmetry.score(pattern)
+                );
+            }
+        }
+    }
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(idx, pattern)| {
+            let symmetries = pattern.mirrors(kinds, smudges);
+            if symmetries.is_empty() {
+                eprintln!("Pattern {} has no symmetry with {} smudges", idx, smudges);
+            }
+            symmetries
+                .iter()
+                .map(|symmetry| symmetry.score(pattern))
+                .sum::<usize>()
+        })
+        .sum()
+}
@@ -0,0 +1,444 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+impl Direction {
+    pub fn parse(s: &str) -> Result<Direction, String> {
+        match s {
+            "N" | "n" | "north" => Ok(Direction::North),
+            "W" | "w" | "west" => Ok(Direction::West),
+            "S" | "s" | "south" => Ok(Direction::South),
+            "E" | "e" | "east" => Ok(Direction::East),
+            _ => Err(format!("Unknown direction {:?}, expected N, W, S or E", s)),
+        }
+    }
+}
+
+/// A tilt program, such as `N W S E`.
+pub fn parse_program(s: &str) -> Result<Vec<Direction>, String> {
+    let program = s
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(Direction::parse)
+        .collect::<Result<Vec<Direction>, String>>()?;
+    if program.is_empty() {
+        return Err("Empty tilt program".to_string());
+    }
+    Ok(program)
+}
+
+/// What to run on the platform, from the command line: `--program <tilts>`
+/// gives the tilts, `--times <k>` how many times to run them, `--load
+/// <edge>` the edge whose support beams are weighed, and `--print` prints
+/// the final platform.
+pub struct Command {
+    pub program: Vec<Direction>,
+    pub times: u64,
+    pub edge: Direction,
+    pub print: bool,
+}
+
+pub fn read_command(default_program: &[Direction], default_times: u64) -> Command {
+    let args = std::env::args().collect::<Vec<String>>();
+    let value = |name: &str| {
+        let idx = args.iter().position(|arg| arg == name)?;
+        Some(
+            args.get(idx + 1)
+                .unwrap_or_else(|| panic!("{} expects a value", name))
+                .as_str(),
+        )
+    };
+    let exit = |err: String| -> ! {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    };
+    Command {
+        program: value("--program").map_or(default_program.to_vec(), |program| {
+            parse_program(program).unwrap_or_else(|err| exit(err))
+        }),
+        times: value("--times").map_or(default_times, |times| {
+            times
+                .parse::<u64>()
+                .unwrap_or_else(|_| exit(format!("Invalid number of times {:?}", times)))
+        }),
+        edge: value("--load").map_or(Direction::North, |edge| {
+            Direction::parse(edge).unwrap_or_else(|err| exit(err))
+        }),
+        print: args.iter().any(|arg| arg == "--print"),
+    }
+}
+
+/// Lines of cells stored as bitsets in one buffer, `stride` words per line,
+/// where bit `i` of a line is its `i`-th cell.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Lines {
+    len: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+/// `n` set bits, from the lowest one.
+fn ones(n: usize) -> u64 {
+    u64::MAX.checked_shr(64 - n as u32).unwrap_or(0)
+}
+
+/// Mask of the bits of a word inside the cells `start..end` of its line.
+fn range_mask(word: usize, start: usize, end: usize) -> u64 {
+    ones((end - word * 64).min(64)) & !ones(start.saturating_sub(word * 64))
+}
+
+impl Lines {
+    pub fn new(num_lines: usize, len: usize) -> Lines {
+        let stride = len.div_ceil(64);
+        Lines {
+            len,
+            stride,
+            words: vec![0; num_lines * stride],
+        }
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.words.len().checked_div(self.stride).unwrap_or(0)
+    }
+
+    fn line(&self, line: usize) -> &[u64] {
+        &self.words[line * self.stride..(line + 1) * self.stride]
+    }
+
+    fn line_mut(&mut self, line: usize) -> &mut [u64] {
+        &mut self.words[line * self.stride..(line + 1) * self.stride]
+    }
+
+    pub fn get(&self, line: usize, i: usize) -> bool {
+        self.line(line)[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, line: usize, i: usize) {
+        self.line_mut(line)[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn count(&self, line: usize) -> usize {
+        self.line(line)
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Indices of the set cells of a line, in increasing order.
+    pub fn ones(&self, line: usize) -> impl Iterator<Item = usize> + '_ {
+        self.line(line).iter().enumerate().flat_map(|(idx, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(idx * 64 + bit)
+            })
+        })
+    }
+
+    /// Move the set cells of `start..end` of a line to the start or the end
+    /// of the range.
+    fn pack(&mut self, line: usize, start: usize, end: usize, toward_start: bool) {
+        let words = self.line_mut(line);
+        let mut count = 0;
+        for (word, value) in words
+            .iter_mut()
+            .enumerate()
+            .take(end.div_ceil(64))
+            .skip(start / 64)
+        {
+            let mask = range_mask(word, start, end);
+            count += (*value & mask).count_ones() as usize;
+            *value &= !mask;
+        }
+        let (from, to) = if toward_start {
+            (start, start + count)
+        } else {
+            (end - count, end)
+        };
+        for (word, value) in words
+            .iter_mut()
+            .enumerate()
+            .take(to.div_ceil(64))
+            .skip(from / 64)
+        {
+            *value |= range_mask(word, from, to);
+        }
+    }
+
+    /// Write the transpose of the lines into `other`, 64x64 blocks at a
+    /// time.
+    fn transpose(&self, other: &mut Lines) {
+        let mut block = [0; 64];
+        for i in 0..self.stride.min(other.num_lines().div_ceil(64)) {
+            for j in 0..other.stride {
+                let lines = j * 64..self.num_lines().min(j * 64 + 64);
+                let num_lines = lines.len();
+                for (k, line) in lines.enumerate() {
+                    block[k] = self.words[line * self.stride + i];
+                }
+                block[num_lines..].fill(0);
+                transpose_block(&mut block);
+                for (k, word) in block.iter().enumerate() {
+                    let line = i * 64 + k;
+                    if line < other.num_lines() {
+                        other.words[line * other.stride + j] = *word;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Transpose a 64x64 bit matrix in place, where bit `j` of `block[i]` is
+/// the cell at row `i` and column `j`.
+fn transpose_block(block: &mut [u64; 64]) {
+    // Swap the off-diagonal quadrants of blocks of size 32, then 16, ...
+    let mut size = 32;
+    let mut mask: u64 = 0x0000_0000_FFFF_FFFF;
+    while size != 0 {
+        let mut i = 0;
+        while i < 64 {
+            for k in i..i + size {
+                let swap = ((block[k] >> size) ^ block[k + size]) & mask;
+                block[k] ^= swap << size;
+                block[k + size] ^= swap;
+            }
+            i += 2 * size;
+        }
+        size /= 2;
+        mask ^= mask << size;
+    }
+}
+
+/// A range of cells without cube rocks, where round rocks roll, inside one
+/// word of the buffer of some lines.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    word: usize,
+    low: usize,
+    len: usize,
+}
+
+/// The free ranges of every line. Most of them fit in one word and are
+/// packed with a few bit operations, the others go through `Lines::pack`.
+#[derive(Clone, Debug)]
+struct Segments {
+    short: Vec<Segment>,
+    long: Vec<(usize, usize, usize)>,
+}
+
+impl Segments {
+    fn new(cubes: &Lines) -> Segments {
+        let mut res = Segments {
+            short: Vec::new(),
+            long: Vec::new(),
+        };
+        for line in 0..cubes.num_lines() {
+            let mut start = 0;
+            for cube in cubes.ones(line).chain(std::iter::once(cubes.len)) {
+                if cube > start && start / 64 == (cube - 1) / 64 {
+                    res.short.push(Segment {
+                        word: line * cubes.stride + start / 64,
+                        low: start % 64,
+                        len: cube - start,
+                    });
+                } else if cube > start {
+                    res.long.push((line, start, cube));
+                }
+                start = cube + 1;
+            }
+        }
+        res
+    }
+
+    fn pack(&self, lines: &mut Lines, toward_start: bool) {
+        // Segments are sorted by word, so each word is loaded and stored
+        // once, and its segments are packed independently of each other.
+        let mut i = 0;
+        while i < self.short.len() {
+            let word = self.short[i].word;
+            let value = lines.words[word];
+            let mut packed = value;
+            while let Some(segment) = self.short.get(i).filter(|segment| segment.word == word) {
+                let mask = ones(segment.len) << segment.low;
+                let count = (value & mask).count_ones() as usize;
+                let shift = if toward_start {
+                    segment.low
+                } else {
+                    segment.low + segment.len - count
+                };
+                packed = (packed & !mask) | ones(count).checked_shl(shift as u32).unwrap_or(0);
+                i += 1;
+            }
+            lines.words[word] = packed;
+        }
+        for &(line, start, end) in &self.long {
+            lines.pack(line, start, end, toward_start);
+        }
+    }
+}
+
+/// A platform of round rocks (`O`) and cube rocks (`#`). Round rocks are
+/// stored both as one bitset per row and one per column, so that tilting
+/// along any direction works on whole lines. Cube rocks never move, so
+/// they are only kept as the free segments of each line.
+#[derive(Clone, Debug)]
+pub struct Platform {
+    pub width: usize,
+    pub height: usize,
+    pub rows: Lines,
+    pub columns: Lines,
+    pub cube_rows: Lines,
+    row_segments: Segments,
+    column_segments: Segments,
+}
+
+impl Platform {
+    pub fn new(input: &str) -> Platform {
+        let lines = input.lines().collect::<Vec<&str>>();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.len());
+        let mut rows = Lines::new(height, width);
+        let mut columns = Lines::new(width, height);
+        let mut cube_rows = Lines::new(height, width);
+        let mut cube_columns = Lines::new(width, height);
+        for (y, line) in lines.iter().enumerate() {
+            assert_eq!(
+                line.len(),
+                width,
+                "Rows of the platform should have the same length"
+            );
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    'O' => {
+                        rows.set(y, x);
+                        columns.set(x, y);
+                    }
+                    '#' => {
+                        cube_rows.set(y, x);
+                        cube_columns.set(x, y);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Platform {
+            width,
+            height,
+            row_segments: Segments::new(&cube_rows),
+            column_segments: Segments::new(&cube_columns),
+            rows,
+            columns,
+            cube_rows,
+        }
+    }
+
+    /// Roll every round rock as far as it goes in the direction. Each free
+    /// segment of a line keeps its number of rocks, packed at one end.
+    pub fn tilt(&mut self, direction: Direction) {
+        let (lines, others, segments) = match direction {
+            Direction::North | Direction::South => {
+                (&mut self.columns, &mut self.rows, &self.column_segments)
+            }
+            Direction::West | Direction::East => {
+                (&mut self.rows, &mut self.columns, &self.row_segments)
+            }
+        };
+        segments.pack(
+            lines,
+            matches!(direction, Direction::North | Direction::West),
+        );
+        lines.transpose(others);
+    }
+
+    pub fn apply(&mut self, program: &[Direction]) {
+        for direction in program {
+            self.tilt(*direction);
+        }
+    }
+
+    /// 128 bits hash of the positions of the round rocks, from two hashes
+    /// with different keys, so that collisions can be ignored.
+    pub fn state_hash(&self) -> u128 {
+        let hash = |key: u64| {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            self.rows.hash(&mut hasher);
+            hasher.finish() as u128
+        };
+        hash(0) << 64 | hash(1)
+    }
+
+    /// Apply the program `times` times. Once a state repeats, the remaining
+    /// repetitions are skipped modulo the period. Only hashes of the past
+    /// states are kept, instead of whole platforms. The running time is thus
+    /// set by how many repetitions it takes to reach a repeated state, not by
+    /// `times`: random 1000x1000 platforms can need thousands of them, each
+    /// dominated by packing the free segments, with the transposes and the
+    /// hash an order of magnitude cheaper.
+    pub fn run(&mut self, program: &[Direction], times: u64) {
+        let mut seen: HashMap<u128, u64> = HashMap::new();
+        let mut done = 0;
+        while done < times {
+            if let Some(previous) = seen.insert(self.state_hash(), done) {
+                let period = done - previous;
+                for _ in 0..(times - done) % period {
+                    self.apply(program);
+                }
+                return;
+            }
+            self.apply(program);
+            done += 1;
+        }
+    }
+
+    /// Load on the support beams of an edge: each round rock weighs its
+    /// distance to the opposite edge, plus one.
+    pub fn load(&self, edge: Direction) -> usize {
+        let (lines, len) = match edge {
+            Direction::North | Direction::South => (&self.rows, self.height),
+            Direction::West | Direction::East => (&self.columns, self.width),
+        };
+        (0..len)
+            .map(|i| {
+                let weight = match edge {
+                    Direction::North | Direction::West => len - i,
+                    Direction::South | Direction::East => i + 1,
+                };
+                lines.count(i) * weight
+            })
+            .sum()
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            let line = (0..self.width)
+                .map(|x| {
+                    if self.rows.get(y, x) {
+                        'O'
+                    } else if self.cube_rows.get(y, x) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
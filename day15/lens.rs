@@ -0,0 +1,412 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+use std::collections::{HashMap, HashSet};
+
+pub const NUM_BOXES: usize = 256;
+
+/// The HASH algorithm: for each character, add its code, multiply by 17
+/// and keep the remainder modulo 256.
+pub fn get_hash(input: &str) -> u8 {
+    let mut val: u8 = 0;
+    for c in input.bytes() {
+        val = val.wrapping_add(c).wrapping_mul(17);
+    }
+    val
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Remove,
+    Insert(u32),
+}
+
+/// One step of the initialization sequence, such as `rn=1` or `cm-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<'a> {
+    pub label: &'a str,
+    pub op: Op,
+}
+
+impl<'a> Step<'a> {
+    pub fn parse(s: &'a str) -> Result<Step<'a>, String> {
+        let s = s.trim();
+        let (label, op) = if let Some((label, focal_length)) = s.split_once('=') {
+            let focal_length = focal_length
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid focal length in step {:?}", s))?;
+            (label, Op::Insert(focal_length))
+        } else if let Some(label) = s.strip_suffix('-') {
+            (label, Op::Remove)
+        } else {
+            return Err(format!(
+                "Step {:?} is neither an insertion nor a removal",
+                s
+            ));
+        };
+        if label.is_empty() {
+            return Err(format!("Step {:?} has no label", s));
+        }
+        Ok(Step { label, op })
+    }
+}
+
+pub fn read_steps(input: &str) -> Result<Vec<Step<'_>>, String> {
+    input.trim().split(',').map(Step::parse).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Lens {
+    pub label: String,
+    pub focal_length: u32,
+}
+
+/// The boxes of lenses, as an ordered hash map. Each box keeps its lenses
+/// in slots, in insertion order, and `index` gives the box and slot of
+/// each label, so that inserting, updating and removing a lens are O(1).
+/// A removed lens leaves an empty slot, and a box is compacted once it has
+/// more empty slots than lenses, which keeps removals amortized O(1).
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct LensBoxes {
+    boxes: Vec<Vec<Option<Lens>>>,
+    num_lenses: Vec<usize>,
+    index: HashMap<String, (usize, usize)>,
+}
+
+#[allow(dead_code)]
+impl LensBoxes {
+    pub fn new() -> LensBoxes {
+        LensBoxes {
+            boxes: vec![Vec::new(); NUM_BOXES],
+            num_lenses: vec![0; NUM_BOXES],
+            index: HashMap::new(),
+        }
+    }
+
+    /// Put a lens in the box of its label, replacing the lens with the same
+    /// label in place if there is one. Return the focal length of the
+    /// replaced lens.
+    pub fn insert(&mut self, label: &str, focal_length: u32) -> Option<u32> {
+        if let Some(&(box_idx, slot)) = self.index.get(label) {
+            let lens = self.boxes[box_idx][slot].as_mut().unwrap();
+            return Some(std::mem::replace(&mut lens.focal_length, focal_length));
+        }
+        let box_idx = get_hash(label) as usize;
+        self.index
+            .insert(label.to_string(), (box_idx, self.boxes[box_idx].len()));
+        self.boxes[box_idx].push(Some(Lens {
+            label: label.to_string(),
+            focal_length,
+        }));
+        self.num_lenses[box_idx] += 1;
+        None
+    }
+
+    /// Take the lens with the label out of its box, if there is one.
+    pub fn remove(&mut self, label: &str) -> Option<Lens> {
+        let (box_idx, slot) = self.index.remove(label)?;
+        let lens = self.boxes[box_idx][slot].take();
+        self.num_lenses[box_idx] -= 1;
+        if self.boxes[box_idx].len() > 2 * self.num_lenses[box_idx] {
+            self.compact(box_idx);
+        }
+        lens
+    }
+
+    fn compact(&mut self, box_idx: usize) {
+        self.boxes[box_idx].retain(Option::is_some);
+        for (slot, lens) in self.boxes[box_idx].iter().enumerate() {
+            let lens = lens.as_ref().unwrap();
+            *self.index.get_mut(&lens.label).unwrap() = (box_idx, slot);
+        }
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        match step.op {
+            Op::Insert(focal_length) => {
+                self.insert(step.label, focal_length);
+            }
+            Op::Remove => {
+                self.remove(step.label);
+            }
+        }
+    }
+
+    /// Lenses of a box, from front to back.
+    pub fn lenses(&self, box_idx: usize) -> impl Iterator<Item = &Lens> {
+        self.boxes[box_idx].iter().flatten()
+    }
+
+    pub fn focusing_power(&self) -> u64 {
+        (0..NUM_BOXES)
+            .flat_map(|box_idx| {
+                self.lenses(box_idx).enumerate().map(move |(slot, lens)| {
+                    (box_idx as u64 + 1) * (slot as u64 + 1) * lens.focal_length as u64
+                })
+            })
+            .sum()
+    }
+
+    /// Print the non-empty boxes, as in the puzzle statement.
+    pub fn print(&self) {
+        for box_idx in 0..NUM_BOXES {
+            if self.num_lenses[box_idx] == 0 {
+                continue;
+            }
+            let lenses = self
+                .lenses(box_idx)
+                .map(|lens| format!("[{} {}]", lens.label, lens.focal_length))
+                .collect::<Vec<String>>();
+            println!("Box {}: {}", box_idx, lenses.join(" "));
+        }
+    }
+}
+
+/// One step of HASH from `state` with the character `c`.
+#[allow(dead_code)]
+fn hash_step(state: u8, c: u8) -> u8 {
+    state.wrapping_add(c).wrapping_mul(17)
+}
+
+/// Labels over an alphabet hashing to a target box. `ways[k][h]` is the
+/// number of strings of length `k` leading from the hash `h` to the
+/// target, which prunes the search to characters that can still reach it.
+#[allow(dead_code)]
+pub struct Preimages {
+    alphabet: Vec<u8>,
+    ways: Vec<Vec<BigUint>>,
+}
+
+#[allow(dead_code)]
+impl Preimages {
+    pub fn new(target: u8, max_len: usize, alphabet: &str) -> Preimages {
+        let mut alphabet = alphabet.bytes().collect::<Vec<u8>>();
+        alphabet.sort();
+        alphabet.dedup();
+        let mut ways = vec![(0..NUM_BOXES)
+            .map(|h| BigUint::from((h == target as usize) as u32))
+            .collect::<Vec<BigUint>>()];
+        for k in 1..=max_len {
+            let next = (0..NUM_BOXES)
+                .map(|h| {
+                    alphabet
+                        .iter()
+                        .map(|&c| &ways[k - 1][hash_step(h as u8, c) as usize])
+                        .sum::<BigUint>()
+                })
+                .collect();
+            ways.push(next);
+        }
+        Preimages { alphabet, ways }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.ways.len() - 1
+    }
+
+    /// Number of non-empty labels of length `len` hashing to the target.
+    pub fn count_of_len(&self, len: usize) -> &BigUint {
+        &self.ways[len][0]
+    }
+
+    pub fn count(&self) -> BigUint {
+        (1..=self.max_len()).map(|len| self.count_of_len(len)).sum()
+    }
+
+    /// The first `limit` labels, shortest first and then in alphabetical
+    /// order.
+    pub fn first(&self, limit: usize) -> Vec<String> {
+        let mut res = Vec::new();
+        for len in 1..=self.max_len() {
+            self.search(0, len, &mut Vec::new(), limit, &mut res);
+        }
+        res
+    }
+
+    fn search(
+        &self,
+        state: u8,
+        left: usize,
+        label: &mut Vec<u8>,
+        limit: usize,
+        res: &mut Vec<String>,
+    ) {
+        if res.len() >= limit {
+            return;
+        }
+        if left == 0 {
+            res.push(String::from_utf8(label.clone()).unwrap());
+            return;
+        }
+        for &c in &self.alphabet {
+            let next = hash_step(state, c);
+            if !self.ways[left - 1][next as usize].is_zero() {
+                label.push(c);
+                self.search(next, left - 1, label, limit, res);
+                label.pop();
+            }
+        }
+    }
+}
+
+/// An initialization sequence inserting `count` distinct labels, all in
+/// the target box, using the shortest labels possible. Every insertion
+/// lands at the back of the same box, and every later update or removal
+/// of these labels has to find its lens in that box.
+#[allow(dead_code)]
+pub fn collision_sequence(target: u8, count: usize, alphabet: &str) -> Result<String, String> {
+    let mut max_len = 1;
+    let preimages = loop {
+        let preimages = Preimages::new(target, max_len, alphabet);
+        if preimages.count() >= BigUint::from(count) {
+            break preimages;
+        }
+        if max_len >= 16 {
+            return Err(format!(
+                "Less than {} labels of at most {} characters hash to box {}",
+                count, max_len, target
+            ));
+        }
+        max_len += 1;
+    };
+    Ok(preimages
+        .first(count)
+        .iter()
+        .enumerate()
+        .map(|(i, label)| format!("{}={}", label, i % 9 + 1))
+        .collect::<Vec<String>>()
+        .join(","))
+}
+
+/// How the distinct labels of a sequence spread over the boxes.
+#[allow(dead_code)]
+pub struct HashStats {
+    pub num_steps: usize,
+    pub per_box: Vec<usize>,
+}
+
+#[allow(dead_code)]
+impl HashStats {
+    pub fn new(steps: &[Step]) -> HashStats {
+        let labels = steps
+            .iter()
+            .map(|step| step.label)
+            .collect::<HashSet<&str>>();
+        let mut per_box = vec![0; NUM_BOXES];
+        for label in labels {
+            per_box[get_hash(label) as usize] += 1;
+        }
+        HashStats {
+            num_steps: steps.len(),
+            per_box,
+        }
+    }
+
+    pub fn num_labels(&self) -> usize {
+        self.per_box.iter().sum()
+    }
+
+    /// Chi-squared statistic of the box counts against a uniform spread,
+    /// which is around 255 for a good hash.
+    pub fn chi_squared(&self) -> f64 {
+        let expected = self.num_labels() as f64 / NUM_BOXES as f64;
+        self.per_box
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    pub fn print(&self) {
+        let (fullest, max_load) = self
+            .per_box
+            .iter()
+            .enumerate()
+            .max_by_key(|(box_idx, count)| (**count, std::cmp::Reverse(*box_idx)))
+            .unwrap();
+        let num_empty = self.per_box.iter().filter(|&&count| count == 0).count();
+        println!(
+            "{} steps, {} distinct labels",
+            self.num_steps,
+            self.num_labels()
+        );
+        println!(
+            "{} empty boxes, fullest box {} with {} labels",
+            num_empty, fullest, max_load
+        );
+        println!(
+            "Mean load {:.2}, chi-squared {:.1}",
+            self.num_labels() as f64 / NUM_BOXES as f64,
+            self.chi_squared()
+        );
+        let mut histogram = HashMap::new();
+        for &count in &self.per_box {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        let mut histogram = histogram.into_iter().collect::<Vec<(usize, usize)>>();
+        histogram.sort();
+        for (load, num_boxes) in histogram {
+            println!("{:>4} boxes with {} labels", num_boxes, load);
+        }
+    }
+}
+
+/// Handle the HASH tools given on the command line:
+/// `--preimages <box>` lists labels hashing to the box, up to `--max-len
+/// <n>` characters (4 by default) and `--limit <k>` labels (20 by
+/// default). `--collide <box> <count>` prints a sequence inserting `count`
+/// labels in the box, and `--stats` prints how the labels of the input
+/// spread over the boxes. Labels use the characters of `--alphabet`,
+/// lowercase letters by default. Return whether a tool was used.
+#[allow(dead_code)]
+pub fn run_tools(input: &str) -> bool {
+    let args = std::env::args().collect::<Vec<String>>();
+    let values = |name: &str, num_values: usize| {
+        let idx = args.iter().position(|arg| arg == name)?;
+        let values = args.get(idx + 1..idx + 1 + num_values);
+        Some(values.unwrap_or_else(|| panic!("{} expects {} values", name, num_values)))
+    };
+    let number = |name: &str, value: &str| {
+        value
+            .parse::<usize>()
+            .unwrap_or_else(|_| panic!("{} expects a number, got {:?}", name, value))
+    };
+    let target = |name: &str, value: &str| {
+        u8::try_from(number(name, value)).unwrap_or_else(|_| panic!("Box {} doesn't exist", value))
+    };
+    let alphabet = values("--alphabet", 1).map_or("abcdefghijklmnopqrstuvwxyz", |x| &x[0]);
+
+    let mut used = false;
+    if let Some(value) = values("--preimages", 1) {
+        let target = target("--preimages", &value[0]);
+        let max_len = values("--max-len", 1).map_or(4, |x| number("--max-len", &x[0]));
+        let limit = values("--limit", 1).map_or(20, |x| number("--limit", &x[0]));
+        let preimages = Preimages::new(target, max_len, alphabet);
+        println!(
+            "{} labels of at most {} characters hash to box {}",
+            preimages.count(),
+            max_len,
+            target
+        );
+        for label in preimages.first(limit) {
+            println!("{}", label);
+        }
+        used = true;
+    }
+    if let Some(value) = values("--collide", 2) {
+        let target = target("--collide", &value[0]);
+        match collision_sequence(target, number("--collide", &value[1]), alphabet) {
+            Ok(sequence) => println!("{}", sequence),
+            Err(err) => eprintln!("{}", err),
+        }
+        used = true;
+    }
+    if args.iter().any(|arg| arg == "--stats") {
+        match read_steps(input) {
+            Ok(steps) => HashStats::new(&steps).print(),
+            Err(err) => eprintln!("{}", err),
+        }
+        used = true;
+    }
+    used
+}
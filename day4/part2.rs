@@ -1,3 +1,5 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
 use std::collections::HashSet;
 
 const FILENAME: &'static str = "day4/part1.in";
@@ -29,35 +31,84 @@ fn get_numbers_from_file(input: &str) -> Vec<(HashSet<i32>, Vec<i32>)> {
     res
 }
 
-fn add_winning_card_to_collection(
-    cards: &mut Vec<i32>,
-    start: i32,
-    num_cards: i32,
-    multiplier: i32,
-) {
-    for i in start..(start + num_cards) {
-        cards[i as usize] += multiplier;
-    }
+/// Copies contributed to a card by the card at index `from`.
+struct Contribution {
+    from: usize,
+    copies: BigUint,
 }
 
-fn get_num_winning_numbers(winning_numbers: &HashSet<i32>, numbers: &Vec<i32>) -> i32 {
-    let mut res = 0;
-    for number in numbers {
-        if winning_numbers.contains(number) {
-            res += 1;
+/// Compute the number of copies of each card in a single pass.
+/// Each card adds its copy count to a range of following cards, which is
+/// recorded in a difference array so that long win chains stay linear.
+/// Copy counts can double at each card of a chain, so they are big
+/// integers. If `trace` is set, also return for each card the cards that
+/// contributed copies to it.
+fn count_copies(num_winning: &[usize], trace: bool) -> (Vec<BigUint>, Vec<Vec<Contribution>>) {
+    let n = num_winning.len();
+    let mut delta = vec![BigUint::zero(); n + 1];
+    let mut removed = vec![BigUint::zero(); n + 1];
+    let mut copies = Vec::with_capacity(n);
+    let mut contributions: Vec<Vec<Contribution>> = Vec::new();
+    if trace {
+        contributions.resize_with(n, Vec::new);
+    }
+
+    let mut running = BigUint::zero();
+    for (idx, &num_winning) in num_winning.iter().enumerate() {
+        running += &delta[idx];
+        running -= &removed[idx];
+        let count = &running + 1u32;
+
+        let end = std::cmp::min(idx + 1 + num_winning, n);
+        if idx + 1 < end {
+            delta[idx + 1] += &count;
+            removed[end] += &count;
+            if trace {
+                for target in &mut contributions[(idx + 1)..end] {
+                    target.push(Contribution {
+                        from: idx,
+                        copies: count.clone(),
+                    });
+                }
+            }
         }
+        copies.push(count);
     }
-    res
+    (copies, contributions)
+}
+
+fn get_num_winning_numbers(winning_numbers: &HashSet<i32>, numbers: &[i32]) -> usize {
+    numbers
+        .iter()
+        .filter(|number| winning_numbers.contains(number))
+        .count()
 }
 
 fn main() {
+    let trace = std::env::args().any(|arg| arg == "--trace");
     let cards = get_numbers_from_file(&read_file());
-    let mut num_cards = vec![1; cards.len()];
+    let num_winning = cards
+        .iter()
+        .map(|(winning_numbers, numbers)| get_num_winning_numbers(winning_numbers, numbers))
+        .collect::<Vec<usize>>();
 
-    for (idx, (winning_numbers, numbers)) in cards.iter().enumerate() {
-        let num_winning = get_num_winning_numbers(winning_numbers, numbers);
-        let multiplier = num_cards[idx];
-        add_winning_card_to_collection(&mut num_cards, (idx + 1) as i32, num_winning, multiplier);
+    let (copies, contributions) = count_copies(&num_winning, trace);
+    if trace {
+        for (idx, card_contributions) in contributions.iter().enumerate() {
+            let sources = card_contributions
+                .iter()
+                .map(|c| format!("{}x{}", c.from + 1, c.copies))
+                .collect::<Vec<String>>()
+                .join(" ");
+            println!(
+                "Card {}: {} winning, {} copies, from [{}]",
+                idx + 1,
+                num_winning[idx],
+                copies[idx],
+                sources
+            );
+        }
     }
-    println!("{}", num_cards.iter().sum::<i32>());
+    let total = copies.iter().sum::<BigUint>();
+    println!("{}", total);
 }
@@ -0,0 +1,479 @@
+/// Values handled by the almanac. Composed maps are normalized over this
+/// domain, so every value in it is covered by exactly one piece.
+pub const DOMAIN: Range = Range {
+    begin: 0,
+    end: i64::MAX / 2,
+};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Range {
+    pub begin: i64,
+    pub end: i64,
+}
+
+impl Range {
+    pub fn contains(self, value: i64) -> bool {
+        self.begin <= value && value <= self.end
+    }
+
+    pub fn intersect(self, other: Range) -> Option<Range> {
+        if self.begin > other.end || self.end < other.begin {
+            return None;
+        }
+        Some(Range {
+            begin: std::cmp::max(self.begin, other.begin),
+            end: std::cmp::min(self.end, other.end),
+        })
+    }
+
+    pub fn subtract(self, other: Range) -> Vec<Range> {
+        let intersect = self.intersect(other);
+        if intersect.is_none() {
+            return vec![self];
+        }
+        let intersect = intersect.unwrap();
+        let mut res = Vec::new();
+        if self.begin < intersect.begin {
+            res.push(Range {
+                begin: self.begin,
+                end: intersect.begin - 1,
+            });
+        }
+        if self.end > intersect.end {
+            res.push(Range {
+                begin: intersect.end + 1,
+                end: self.end,
+            });
+        }
+        res
+    }
+
+    fn shift(self, offset: i64) -> Range {
+        Range {
+            begin: self.begin + offset,
+            end: self.end + offset,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SingleRangeMap {
+    pub source_range: Range,
+    pub offset: i64,
+}
+
+impl SingleRangeMap {
+    pub fn map(&self, source: i64) -> Option<i64> {
+        if self.source_range.contains(source) {
+            Some(source + self.offset)
+        } else {
+            None
+        }
+    }
+
+    fn destination_range(&self) -> Range {
+        self.source_range.shift(self.offset)
+    }
+}
+
+/// A piecewise map. Values not covered by any piece are mapped to
+/// themselves, and the first matching piece wins when pieces overlap.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RangeMap(pub Vec<SingleRangeMap>);
+
+impl RangeMap {
+    #[allow(dead_code)]
+    pub fn map(&self, source: i64) -> i64 {
+        for map in &self.0 {
+            if let Some(destination) = map.map(source) {
+                return destination;
+            }
+        }
+        source
+    }
+
+    /// Return an equivalent map with sorted and disjoint pieces covering the
+    /// whole domain. Gaps are filled with the identity, and adjacent pieces
+    /// with the same offset are merged.
+    pub fn normalize(&self) -> NormalizedRangeMap {
+        let mut pieces: Vec<SingleRangeMap> = Vec::new();
+        let mut uncovered = vec![DOMAIN];
+        for map in &self.0 {
+            let mut remaining = Vec::new();
+            for range in uncovered {
+                if let Some(covered) = range.intersect(map.source_range) {
+                    pieces.push(SingleRangeMap {
+                        source_range: covered,
+                        offset: map.offset,
+                    });
+                }
+                remaining.append(&mut range.subtract(map.source_range));
+            }
+            uncovered = remaining;
+        }
+        pieces.extend(uncovered.into_iter().map(|range| SingleRangeMap {
+            source_range: range,
+            offset: 0,
+        }));
+        pieces.sort_by_key(|x| x.source_range.begin);
+
+        let mut res: Vec<SingleRangeMap> = Vec::new();
+        for piece in pieces {
+            match res.last_mut() {
+                Some(last)
+                    if last.offset == piece.offset
+                        && last.source_range.end + 1 == piece.source_range.begin =>
+                {
+                    last.source_range.end = piece.source_range.end;
+                }
+                _ => res.push(piece),
+            }
+        }
+        NormalizedRangeMap(res)
+    }
+}
+
+/// A map whose pieces are sorted, contiguous, and cover the whole domain,
+/// so that every value is in exactly one piece, found by binary search.
+/// It can only be built by `RangeMap::normalize`, which keeps this true.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct NormalizedRangeMap(Vec<SingleRangeMap>);
+
+impl NormalizedRangeMap {
+    pub fn identity() -> NormalizedRangeMap {
+        NormalizedRangeMap(vec![SingleRangeMap {
+            source_range: DOMAIN,
+            offset: 0,
+        }])
+    }
+
+    #[allow(dead_code)]
+    pub fn map(&self, source: i64) -> i64 {
+        let idx = self.0.partition_point(|x| x.source_range.end < source);
+        self.0
+            .get(idx)
+            .and_then(|x| x.map(source))
+            .expect("Value outside of the domain")
+    }
+
+    /// Return the normalized map applying `self`, then `next`.
+    pub fn compose(&self, next: &RangeMap) -> NormalizedRangeMap {
+        let next = next.normalize();
+        let mut res = Vec::new();
+        for piece in &self.0 {
+            let mut remaining = vec![piece.destination_range()];
+            for next_piece in &next.0 {
+                let mut new_remaining = Vec::new();
+                for range in remaining {
+                    if let Some(covered) = range.intersect(next_piece.source_range) {
+                        res.push(SingleRangeMap {
+                            source_range: covered.shift(-piece.offset),
+                            offset: piece.offset + next_piece.offset,
+                        });
+                    }
+                    new_remaining.append(&mut range.subtract(next_piece.source_range));
+                }
+                remaining = new_remaining;
+            }
+            // Values mapped outside of the domain are left untouched by `next`.
+            res.extend(remaining.into_iter().map(|range| SingleRangeMap {
+                source_range: range.shift(-piece.offset),
+                offset: piece.offset,
+            }));
+        }
+        RangeMap(res).normalize()
+    }
+
+    /// Return the map going from destinations back to sources.
+    pub fn invert(&self) -> InvertedRangeMap {
+        InvertedRangeMap(
+            self.0
+                .iter()
+                .map(|piece| SingleRangeMap {
+                    source_range: piece.destination_range(),
+                    offset: -piece.offset,
+                })
+                .collect(),
+        )
+    }
+
+    /// Minimum value of the map over a range. Each piece is increasing, so
+    /// the minimum is reached at the start of the range or at a breakpoint.
+    #[allow(dead_code)]
+    pub fn min_over(&self, range: Range) -> i64 {
+        let first = self.0.partition_point(|x| x.source_range.end < range.begin);
+        self.0[first..]
+            .iter()
+            .take_while(|piece| piece.source_range.begin <= range.end)
+            .filter_map(|piece| {
+                let covered = piece.source_range.intersect(range)?;
+                Some(covered.begin + piece.offset)
+            })
+            .min()
+            .unwrap_or(range.begin)
+    }
+}
+
+/// The inverse of a normalized map. Since the map is not necessarily
+/// injective, pieces can overlap, and a value covered by no piece is not
+/// the image of any value.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct InvertedRangeMap(pub Vec<SingleRangeMap>);
+
+impl InvertedRangeMap {
+    /// Every value mapped to `value`, if any.
+    pub fn preimages(&self, value: i64) -> Vec<i64> {
+        self.0.iter().filter_map(|map| map.map(value)).collect()
+    }
+}
+
+/// A map between two named categories, such as `seed-to-soil`.
+pub struct NamedRangeMap {
+    pub source: String,
+    pub destination: String,
+    pub map: RangeMap,
+}
+
+/// A problem found in the pieces of a map. Pieces are indexed by their
+/// position in the map.
+#[derive(PartialEq, Eq, Debug)]
+pub enum MapIssue {
+    Degenerate {
+        map: String,
+        piece: usize,
+    },
+    Overlap {
+        map: String,
+        first: usize,
+        second: usize,
+    },
+}
+
+impl std::fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapIssue::Degenerate { map, piece } => {
+                write!(f, "{}: range {} is empty", map, piece)
+            }
+            MapIssue::Overlap { map, first, second } => {
+                write!(f, "{}: ranges {} and {} overlap", map, first, second)
+            }
+        }
+    }
+}
+
+impl NamedRangeMap {
+    pub fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+
+    pub fn validate(&self) -> Vec<MapIssue> {
+        let mut res = Vec::new();
+        let pieces = &self.map.0;
+        for (idx, piece) in pieces.iter().enumerate() {
+            if piece.source_range.end < piece.source_range.begin {
+                res.push(MapIssue::Degenerate {
+                    map: self.name(),
+                    piece: idx,
+                });
+                continue;
+            }
+            for (other_idx, other) in pieces.iter().enumerate().skip(idx + 1) {
+                if other.source_range.end >= other.source_range.begin
+                    && piece.source_range.intersect(other.source_range).is_some()
+                {
+                    res.push(MapIssue::Overlap {
+                        map: self.name(),
+                        first: idx,
+                        second: other_idx,
+                    });
+                }
+            }
+        }
+        res
+    }
+}
+
+pub struct RangeMaps(pub Vec<NamedRangeMap>);
+
+impl RangeMaps {
+    pub fn get(&self, source: &str) -> Option<&NamedRangeMap> {
+        self.0.iter().find(|map| map.source == source)
+    }
+
+    /// Follow the maps by category name, from `source` to `destination`.
+    pub fn chain(&self, source: &str, destination: &str) -> Vec<&NamedRangeMap> {
+        let mut res = Vec::new();
+        let mut category = source;
+        while category != destination {
+            let map = self
+                .get(category)
+                .unwrap_or_else(|| panic!("No map from category {}", category));
+            if res.len() == self.0.len() {
+                panic!("Maps from {} contain a cycle", source);
+            }
+            res.push(map);
+            category = &map.destination;
+        }
+        res
+    }
+
+    #[allow(dead_code)]
+    pub fn map(&self, mut source: i64) -> i64 {
+        for map in self.chain("seed", "location") {
+            source = map.map.map(source);
+        }
+        source
+    }
+
+    /// Collapse the maps from `source` to `destination` into a single
+    /// normalized map.
+    pub fn compose_between(&self, source: &str, destination: &str) -> NormalizedRangeMap {
+        self.chain(source, destination)
+            .iter()
+            .fold(NormalizedRangeMap::identity(), |acc, map| {
+                acc.compose(&map.map)
+            })
+    }
+
+    /// Collapse all maps from seeds to locations into a single normalized map.
+    pub fn compose(&self) -> NormalizedRangeMap {
+        self.compose_between("seed", "location")
+    }
+
+    pub fn validate(&self) -> Vec<MapIssue> {
+        self.0.iter().flat_map(|map| map.validate()).collect()
+    }
+
+    /// Export the mapping pipeline as a Graphviz graph. Each map is drawn as
+    /// a table with one band per range, and overlapping or empty ranges are
+    /// highlighted.
+    pub fn to_dot(&self) -> String {
+        let issues = self.validate();
+        let is_flagged = |name: &str, idx: usize| {
+            issues.iter().any(|issue| match issue {
+                MapIssue::Degenerate { map, piece } => map == name && *piece == idx,
+                MapIssue::Overlap { map, first, second } => {
+                    map == name && (*first == idx || *second == idx)
+                }
+            })
+        };
+
+        let mut res = String::from("digraph almanac {\n    rankdir=LR;\n");
+        res.push_str("    node [fontname=\"monospace\"];\n");
+        for map in &self.0 {
+            let name = map.name();
+            res.push_str(&format!(
+                "    \"{}\" [shape=plaintext, label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\">\n",
+                name
+            ));
+            res.push_str(&format!(
+                "        <tr><td colspan=\"3\"><b>{}</b></td></tr>\n",
+                name
+            ));
+            let mut pieces = map.map.0.iter().enumerate().collect::<Vec<_>>();
+            pieces.sort_by_key(|(_, piece)| piece.source_range.begin);
+            for (idx, piece) in pieces {
+                let color = if is_flagged(&name, idx) {
+                    "#f4a6a6"
+                } else if piece.offset < 0 {
+                    "#a6c8f4"
+                } else {
+                    "#b6e3b0"
+                };
+                res.push_str(&format!(
+                    "        <tr><td bgcolor=\"{}\">{}..={}</td><td bgcolor=\"{}\">{:+}</td><td bgcolor=\"{}\">{}..={}</td></tr>\n",
+                    color,
+                    piece.source_range.begin,
+                    piece.source_range.end,
+                    color,
+                    piece.offset,
+                    color,
+                    piece.source_range.begin + piece.offset,
+                    piece.source_range.end + piece.offset,
+                ));
+            }
+            res.push_str("    </table>>];\n");
+            res.push_str(&format!(
+                "    \"{}\" -> \"{}\" -> \"{}\";\n",
+                map.source, name, map.destination
+            ));
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+fn read_three_numbers(s: &str) -> SingleRangeMap {
+    let res = s
+        .trim()
+        .split(" ")
+        .map(|x| x.parse::<i64>().unwrap())
+        .collect::<Vec<i64>>();
+    SingleRangeMap {
+        source_range: Range {
+            begin: res[1],
+            end: res[1] + res[2] - 1,
+        },
+        offset: res[0] - res[1],
+    }
+}
+
+fn read_header(s: &str) -> (String, String) {
+    let name = s
+        .trim()
+        .strip_suffix("map:")
+        .expect("Map header should end with 'map:'")
+        .trim();
+    let (source, destination) = name
+        .split_once("-to-")
+        .expect("Map header should be of the form 'a-to-b map:'");
+    (source.to_string(), destination.to_string())
+}
+
+fn read_one_rangemap(s: &str) -> NamedRangeMap {
+    let (source, destination) = read_header(s.lines().next().unwrap());
+    let mut res = Vec::new();
+    for line in s.lines().skip(1) {
+        res.push(read_three_numbers(line));
+    }
+    NamedRangeMap {
+        source,
+        destination,
+        map: RangeMap(res),
+    }
+}
+
+pub fn read_rangemaps(input: &str) -> RangeMaps {
+    RangeMaps(input.split("\n\n").skip(1).map(read_one_rangemap).collect())
+}
+
+/// Handle the `--validate` and `--dot` arguments. Return whether one of
+/// them was given.
+pub fn run_diagnostics(rangemaps: &RangeMaps) -> bool {
+    let mut res = false;
+    if std::env::args().any(|arg| arg == "--validate") {
+        let issues = rangemaps.validate();
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        println!("{} issue(s) found", issues.len());
+        res = true;
+    }
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", rangemaps.to_dot());
+        res = true;
+    }
+    res
+}
+
+/// Parse the `--locate <location>` argument, if any.
+pub fn read_location_query() -> Option<i64> {
+    let args = std::env::args().collect::<Vec<String>>();
+    let idx = args.iter().position(|arg| arg == "--locate")?;
+    Some(
+        args.get(idx + 1)
+            .and_then(|x| x.parse::<i64>().ok())
+            .expect("--locate expects a location"),
+    )
+}
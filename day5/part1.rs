@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+mod almanac;
+
+use almanac::{read_location_query, read_rangemaps, run_diagnostics};
 
 const FILENAME: &'static str = "day5/part1.in";
 
@@ -6,49 +8,6 @@ fn read_file() -> String {
     std::fs::read_to_string(FILENAME).expect("Something went wrong reading the file")
 }
 
-#[derive(PartialEq, Eq)]
-struct SingleRangeMap {
-    destination_start: i64,
-    source_start: i64,
-    range: i64,
-}
-
-impl SingleRangeMap {
-    fn map(&self, source: i64) -> Option<i64> {
-        if source >= self.source_start && source < self.source_start + self.range {
-            Some(self.destination_start + (source - self.source_start))
-        } else {
-            None
-        }
-    }
-}
-
-struct RangeMap(Vec<SingleRangeMap>);
-
-impl RangeMap {
-    fn map(&self, source: i64) -> i64 {
-        for map in &self.0 {
-            if let Some(destination) = map.map(source) {
-                return destination;
-            }
-        }
-        source
-    }
-}
-
-fn read_three_numbers(s: &str) -> SingleRangeMap {
-    let res = s
-        .trim()
-        .split(" ")
-        .map(|x| x.parse::<i64>().unwrap())
-        .collect::<Vec<i64>>();
-    SingleRangeMap {
-        destination_start: res[0],
-        source_start: res[1],
-        range: res[2],
-    }
-}
-
 fn read_seed_inputs(s: &str) -> Vec<i64> {
     s.trim()
         .split(":")
@@ -61,39 +20,26 @@ fn read_seed_inputs(s: &str) -> Vec<i64> {
         .collect()
 }
 
-fn read_one_rangemap(s: &str) -> RangeMap {
-    let mut res = Vec::new();
-    for line in s.lines().skip(1) {
-        res.push(read_three_numbers(line));
-    }
-    RangeMap(res)
-}
-
-struct RangeMaps(Vec<RangeMap>);
-
-impl RangeMaps {
-    fn map(&self, mut source: i64) -> i64 {
-        for map in &self.0 {
-            source = map.map(source);
-        }
-        source
-    }
-}
-
 fn main() {
     let input = read_file();
     let seeds = read_seed_inputs(input.lines().nth(0).unwrap());
-    let rangemaps = RangeMaps(
-        input
-            .split("\n\n")
-            .skip(1)
-            .map(|x| read_one_rangemap(x))
-            .collect(),
-    );
+    let rangemaps = read_rangemaps(&input);
+    if run_diagnostics(&rangemaps) {
+        return;
+    }
+    let almanac = rangemaps.compose();
+
+    if let Some(location) = read_location_query() {
+        let mut preimages = almanac.invert().preimages(location);
+        preimages.retain(|x| seeds.contains(x));
+        println!("Seeds landing at {}: {:?}", location, preimages);
+        return;
+    }
 
     let mut min_value = i64::MAX;
     for seed in seeds {
-        min_value = std::cmp::min(min_value, rangemaps.map(seed));
+        debug_assert_eq!(almanac.map(seed), rangemaps.map(seed));
+        min_value = std::cmp::min(min_value, almanac.map(seed));
     }
     println!("{}", min_value);
 }
@@ -1,35 +1,23 @@
+mod race;
+
 use itertools::Itertools;
+use race::{print_wins_if_requested, read_model, read_number};
+
 const FILENAME: &'static str = "day6/part1.in";
 
 fn read_file() -> String {
     std::fs::read_to_string(FILENAME).expect("Something went wrong reading the file")
 }
 
-fn read_number(s: &str) -> i64 {
-    s.split_whitespace()
-        .skip(1)
-        .join("")
-        .parse::<i64>()
-        .unwrap()
-}
-
-fn will_win(time: i64, distance: i64, time_pressed: i64) -> bool {
-    (time - time_pressed) * time_pressed > distance
-}
-
-fn how_many_ways_win(time: i64, distance: i64) -> i64 {
-    let mut ways = 0;
-    for time_pressed in 1..time {
-        if will_win(time, distance, time_pressed) {
-            ways += 1;
-        }
-    }
-    ways
+fn read_concatenated_number(s: &str) -> num_bigint::BigUint {
+    read_number(&s.split_whitespace().skip(1).join(""))
 }
 
 fn main() {
     let file = read_file();
-    let time = read_number(file.lines().nth(0).unwrap());
-    let distance = read_number(file.lines().nth(1).unwrap());
-    println!("{}", how_many_ways_win(time, distance));
+    let time = read_concatenated_number(file.lines().nth(0).unwrap());
+    let distance = read_concatenated_number(file.lines().nth(1).unwrap());
+    let model = read_model();
+    print_wins_if_requested(model.as_ref(), &time, &distance);
+    println!("{}", model.count_wins(&time, &distance));
 }
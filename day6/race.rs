@@ -0,0 +1,300 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+fn will_win(time: &BigUint, distance: &BigUint, time_pressed: &BigUint) -> bool {
+    time_pressed <= time && (time - time_pressed) * time_pressed > *distance
+}
+
+/// The hold times `t` such that `t * (time - t) > distance`, as an
+/// inclusive interval. The winning hold times are centered on `time / 2`,
+/// and the bounds are the roots of `t^2 - time * t + distance`. The lower
+/// bound is estimated with an integer square root, then corrected by a few
+/// steps.
+fn winning_holds(time: &BigUint, distance: &BigUint) -> Option<(BigUint, BigUint)> {
+    let zero = BigUint::from(0u32);
+    let one = BigUint::from(1u32);
+    let half = time / 2u32;
+    if !will_win(time, distance, &half) {
+        return None;
+    }
+
+    let square = time * time;
+    let four_distance = distance * 4u32;
+    let root = if square > four_distance {
+        (square - four_distance).sqrt()
+    } else {
+        zero.clone()
+    };
+    let mut first = if *time > root {
+        (time - &root) / 2u32
+    } else {
+        zero.clone()
+    };
+    while !will_win(time, distance, &first) {
+        first += &one;
+    }
+    while first > zero && will_win(time, distance, &(&first - &one)) {
+        first -= &one;
+    }
+
+    // Winning hold times are symmetric around time / 2.
+    let last = time - &first;
+    Some((first, last))
+}
+
+/// Count the hold times `t` such that `t * (time - t) > distance`.
+pub fn how_many_ways_win(time: &BigUint, distance: &BigUint) -> BigUint {
+    match winning_holds(time, distance) {
+        Some((first, last)) => last - first + 1u32,
+        None => BigUint::from(0u32),
+    }
+}
+
+/// Enumerating strategies is only done when there are at most this many.
+const MAX_STRATEGIES: u64 = 100_000_000;
+
+/// Number of ways to split `time` into `num_phases` phase durations, plus
+/// the implicit last phase, that is `C(time + num_phases, num_phases)`.
+fn num_strategies(time: u64, num_phases: usize) -> BigUint {
+    let mut res = BigUint::from(1u32);
+    for idx in 1..=num_phases as u64 {
+        res = res * (time + idx) / idx;
+    }
+    res
+}
+
+/// A way of turning the time spent charging into distance. A strategy is
+/// a list of phase durations, alternating between charging and coasting and
+/// starting with charging. The last coasting phase is implicit, and lasts
+/// until the end of the race.
+pub trait BoatModel {
+    fn num_phases(&self) -> usize {
+        1
+    }
+
+    /// Distance travelled during a race with the given strategy.
+    fn distance(&self, time: u64, phases: &[u64]) -> u128;
+
+    /// Call `f` on every winning strategy. This enumerates all strategies,
+    /// so it is exponential in the number of phases, and refuses to run when
+    /// there are more than `MAX_STRATEGIES` of them.
+    fn for_each_win(&self, time: u64, distance: u128, f: &mut dyn FnMut(&[u64])) {
+        let strategies = num_strategies(time, self.num_phases());
+        if strategies > BigUint::from(MAX_STRATEGIES) {
+            panic!(
+                "Too many strategies to enumerate ({}) for a race of {}ms with {} phases",
+                strategies,
+                time,
+                self.num_phases()
+            );
+        }
+        let mut phases = Vec::with_capacity(self.num_phases());
+        for_each_strategy(time, self.num_phases(), &mut phases, &mut |phases| {
+            if self.distance(time, phases) > distance {
+                f(phases);
+            }
+        });
+    }
+
+    fn count_wins(&self, time: &BigUint, distance: &BigUint) -> BigUint {
+        let time = time
+            .to_u64()
+            .expect("Race time is too large for this model");
+        let distance = distance
+            .to_u128()
+            .expect("Race distance is too large for this model");
+        let mut res = 0u64;
+        self.for_each_win(time, distance, &mut |_| res += 1);
+        BigUint::from(res)
+    }
+}
+
+fn for_each_strategy(
+    remaining: u64,
+    num_phases: usize,
+    phases: &mut Vec<u64>,
+    f: &mut dyn FnMut(&[u64]),
+) {
+    if phases.len() == num_phases {
+        f(phases);
+        return;
+    }
+    for duration in 0..=remaining {
+        phases.push(duration);
+        for_each_strategy(remaining - duration, num_phases, phases, f);
+        phases.pop();
+    }
+}
+
+fn charge_then_coast(time: u64, hold: u64, speed: u128) -> u128 {
+    speed * (time.saturating_sub(hold) as u128)
+}
+
+/// The boat gains `rate` speed per millisecond of charging. A rate of 1 is
+/// the model of the puzzle.
+pub struct Accelerating {
+    pub rate: u64,
+}
+
+impl Default for Accelerating {
+    fn default() -> Self {
+        Accelerating { rate: 1 }
+    }
+}
+
+impl BoatModel for Accelerating {
+    fn distance(&self, time: u64, phases: &[u64]) -> u128 {
+        charge_then_coast(time, phases[0], (self.rate * phases[0]) as u128)
+    }
+
+    /// `rate * x > distance` exactly when `x > distance / rate`, so the
+    /// closed form of the unit model can be reused.
+    fn count_wins(&self, time: &BigUint, distance: &BigUint) -> BigUint {
+        if self.rate == 0 {
+            return BigUint::from(0u32);
+        }
+        how_many_ways_win(time, &(distance / self.rate))
+    }
+}
+
+/// Like `Accelerating`, but the speed can't go over `max_speed`.
+pub struct SpeedCap {
+    pub rate: u64,
+    pub max_speed: u64,
+}
+
+impl BoatModel for SpeedCap {
+    fn distance(&self, time: u64, phases: &[u64]) -> u128 {
+        let speed = std::cmp::min(self.rate * phases[0], self.max_speed);
+        charge_then_coast(time, phases[0], speed as u128)
+    }
+
+    /// While the speed is under the cap this is the `Accelerating` model.
+    /// Once it is capped, holding for `h` wins when
+    /// `max_speed * (time - h) > distance`, that is when
+    /// `h < time - distance / max_speed`.
+    fn count_wins(&self, time: &BigUint, distance: &BigUint) -> BigUint {
+        let zero = BigUint::from(0u32);
+        if self.rate == 0 || self.max_speed == 0 {
+            return zero;
+        }
+        let uncapped = BigUint::from(self.max_speed / self.rate);
+
+        let mut res = match winning_holds(time, &(distance / self.rate)) {
+            Some((first, last)) if first <= uncapped => {
+                std::cmp::min(last, uncapped.clone()) - first + 1u32
+            }
+            _ => zero.clone(),
+        };
+
+        // Winning capped hold times are in `uncapped + 1 ..= time - coast`.
+        let coast = distance / self.max_speed + 1u32;
+        if *time > &coast + &uncapped {
+            res += time - coast - uncapped;
+        }
+        res
+    }
+}
+
+/// Like `Accelerating`, but the boat needs `penalty` milliseconds after
+/// charging before it starts moving.
+pub struct ChargingPenalty {
+    pub rate: u64,
+    pub penalty: u64,
+}
+
+impl BoatModel for ChargingPenalty {
+    fn distance(&self, time: u64, phases: &[u64]) -> u128 {
+        let speed = (self.rate * phases[0]) as u128;
+        charge_then_coast(time, phases[0] + self.penalty, speed)
+    }
+
+    /// The boat only moves for `time - penalty` milliseconds, so this is
+    /// the `Accelerating` model on a shorter race.
+    fn count_wins(&self, time: &BigUint, distance: &BigUint) -> BigUint {
+        let penalty = BigUint::from(self.penalty);
+        if self.rate == 0 || *time <= penalty {
+            return BigUint::from(0u32);
+        }
+        how_many_ways_win(&(time - penalty), &(distance / self.rate))
+    }
+}
+
+/// The boat alternates `stages` charging phases with coasting phases.
+/// It stops while charging, and each charge adds to the current speed.
+pub struct MultiStage {
+    pub rate: u64,
+    pub stages: usize,
+}
+
+impl BoatModel for MultiStage {
+    fn num_phases(&self) -> usize {
+        2 * self.stages - 1
+    }
+
+    fn distance(&self, time: u64, phases: &[u64]) -> u128 {
+        let mut speed = 0u128;
+        let mut res = 0u128;
+        for (idx, duration) in phases.iter().enumerate() {
+            if idx % 2 == 0 {
+                speed += (self.rate * duration) as u128;
+            } else {
+                res += speed * (*duration as u128);
+            }
+        }
+        res + charge_then_coast(time, phases.iter().sum(), speed)
+    }
+}
+
+/// Parse the `--model <model>` argument. Models are written `accel:RATE`,
+/// `cap:RATE:MAX_SPEED`, `penalty:RATE:PENALTY` or `stages:RATE:STAGES`.
+pub fn read_model() -> Box<dyn BoatModel> {
+    let args = std::env::args().collect::<Vec<String>>();
+    let Some(idx) = args.iter().position(|arg| arg == "--model") else {
+        return Box::new(Accelerating::default());
+    };
+    let model = args.get(idx + 1).expect("--model expects a model");
+    let parts = model.split(':').collect::<Vec<&str>>();
+    let param = |idx: usize| -> u64 {
+        parts
+            .get(idx)
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or_else(|| panic!("Invalid model parameters in {}", model))
+    };
+    match parts[0] {
+        "accel" => Box::new(Accelerating { rate: param(1) }),
+        "cap" => Box::new(SpeedCap {
+            rate: param(1),
+            max_speed: param(2),
+        }),
+        "penalty" => Box::new(ChargingPenalty {
+            rate: param(1),
+            penalty: param(2),
+        }),
+        "stages" if param(2) > 0 => Box::new(MultiStage {
+            rate: param(1),
+            stages: param(2) as usize,
+        }),
+        _ => panic!("Unknown boat model {}", model),
+    }
+}
+
+/// Print the winning strategies of a race if `--enumerate` was given.
+pub fn print_wins_if_requested(model: &dyn BoatModel, time: &BigUint, distance: &BigUint) {
+    if !std::env::args().any(|arg| arg == "--enumerate") {
+        return;
+    }
+    let time = time.to_u64().expect("Race time is too large to enumerate");
+    let distance = distance
+        .to_u128()
+        .expect("Race distance is too large to enumerate");
+    println!("Race of {}ms, record {}mm:", time, distance);
+    model.for_each_win(time, distance, &mut |phases| {
+        println!("    {:?} -> {}mm", phases, model.distance(time, phases));
+    });
+}
+
+pub fn read_number(s: &str) -> BigUint {
+    s.parse::<BigUint>()
+        .expect("Expected a non-negative integer")
+}
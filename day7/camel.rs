@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A hand category, described by the sizes of its groups of equal cards,
+/// from largest to smallest. A full house is `[3, 2]`.
+#[derive(Clone, Debug)]
+pub struct Category {
+    pub name: String,
+    pub groups: Vec<usize>,
+}
+
+impl Category {
+    /// Whether a hand with the given group sizes (from largest to
+    /// smallest) can form this category using `num_wild` wild cards.
+    /// Pairing the sorted groups greedily is optimal, so wild cards only
+    /// need to cover the missing cards of each group.
+    fn is_reachable(&self, groups: &[usize], num_wild: usize) -> bool {
+        let missing = self
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(i, size)| size.saturating_sub(*groups.get(i).unwrap_or(&0)))
+            .sum::<usize>();
+        missing <= num_wild
+    }
+}
+
+/// Rules of a Camel Cards game.
+#[derive(Clone, Debug)]
+pub struct CamelRules {
+    /// Cards from weakest to strongest.
+    pub card_order: Vec<char>,
+    /// Cards that act as whichever card makes the best category.
+    pub wildcards: Vec<char>,
+    pub hand_size: usize,
+    /// Categories from weakest to strongest.
+    pub categories: Vec<Category>,
+}
+
+impl CamelRules {
+    #[allow(dead_code)]
+    pub fn part1() -> CamelRules {
+        CamelRules {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            categories: CamelRules::standard_categories(5),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn part2() -> CamelRules {
+        CamelRules {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            hand_size: 5,
+            categories: CamelRules::standard_categories(5),
+        }
+    }
+
+    /// One category per way of splitting `hand_size` cards into groups,
+    /// ordered so that larger groups come first. For 5 cards, this is the
+    /// usual order from high card to five of a kind.
+    pub fn standard_categories(hand_size: usize) -> Vec<Category> {
+        let mut partitions = Vec::new();
+        partitions_of(hand_size, hand_size, &mut Vec::new(), &mut partitions);
+        partitions.sort();
+        partitions
+            .into_iter()
+            .map(|groups| Category {
+                name: category_name(&groups),
+                groups,
+            })
+            .collect()
+    }
+
+    /// Override the preset with the `--wild <cards>`, `--order <cards>` and
+    /// `--hand-size <n>` arguments. Changing the hand size also resets the
+    /// categories to the standard ones for that size.
+    pub fn with_args(mut self) -> CamelRules {
+        let args = std::env::args().collect::<Vec<String>>();
+        let value = |name: &str| {
+            let idx = args.iter().position(|arg| arg == name)?;
+            Some(
+                args.get(idx + 1)
+                    .unwrap_or_else(|| panic!("{} expects a value", name))
+                    .clone(),
+            )
+        };
+        if let Some(order) = value("--order") {
+            self.card_order = order.chars().collect();
+        }
+        if let Some(wildcards) = value("--wild") {
+            self.wildcards = wildcards.chars().collect();
+        }
+        if let Some(hand_size) = value("--hand-size") {
+            self.hand_size = hand_size.parse::<usize>().expect("Invalid hand size");
+            self.categories = CamelRules::standard_categories(self.hand_size);
+        }
+        self
+    }
+
+    pub fn card_value(&self, card: char) -> usize {
+        self.card_order
+            .iter()
+            .position(|&x| x == card)
+            .unwrap_or_else(|| panic!("Unknown card {}", card))
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    /// Groups of equal non-wild cards, from largest to smallest and then
+    /// from strongest to weakest card, and the number of wild cards.
+    fn get_groups(&self, cards: &[char]) -> (Vec<(char, usize)>, usize) {
+        let mut counts = HashMap::new();
+        let mut num_wild = 0;
+        for &card in cards {
+            if self.is_wild(card) {
+                num_wild += 1;
+            } else {
+                *counts.entry(card).or_insert(0) += 1;
+            }
+        }
+        let mut groups = counts.into_iter().collect::<Vec<(char, usize)>>();
+        groups.sort_by(|(card, count), (other_card, other_count)| {
+            other_count
+                .cmp(count)
+                .then_with(|| self.card_value(*other_card).cmp(&self.card_value(*card)))
+        });
+        (groups, num_wild)
+    }
+
+    /// Index in `categories` of the best category the cards can form.
+    pub fn get_category(&self, cards: &[char]) -> usize {
+        let (groups, num_wild) = self.get_groups(cards);
+        let groups = groups.iter().map(|(_, count)| *count).collect::<Vec<_>>();
+        self.categories
+            .iter()
+            .rposition(|category| category.is_reachable(&groups, num_wild))
+            .expect("A hand should form at least one category")
+    }
+
+    /// The card each wild card of the hand stands for, in order of
+    /// appearance. Wild cards complete the groups of the category from the
+    /// largest to the smallest, and new groups use the strongest cards
+    /// missing from the hand. Extra wild cards join the largest group.
+    pub fn get_substitutes(&self, cards: &[char], category: usize) -> Vec<(char, char)> {
+        let (groups, mut num_wild) = self.get_groups(cards);
+        let mut unused = self
+            .card_order
+            .iter()
+            .rev()
+            .filter(|&&card| !self.is_wild(card) && !groups.iter().any(|(x, _)| *x == card));
+        let mut substitutes = Vec::new();
+        for (i, size) in self.categories[category].groups.iter().enumerate() {
+            let (card, count) = match groups.get(i) {
+                Some(&(card, count)) => (card, count),
+                None => match unused.next() {
+                    Some(&card) => (card, 0),
+                    None => break,
+                },
+            };
+            let missing = std::cmp::min(size.saturating_sub(count), num_wild);
+            substitutes.extend(std::iter::repeat_n(card, missing));
+            num_wild -= missing;
+        }
+        if let Some(&first) = substitutes.first().or(groups.first().map(|(card, _)| card)) {
+            substitutes.extend(std::iter::repeat_n(first, num_wild));
+        }
+        cards
+            .iter()
+            .filter(|&&card| self.is_wild(card))
+            .zip(substitutes)
+            .map(|(&wild, substitute)| (wild, substitute))
+            .collect()
+    }
+}
+
+fn partitions_of(n: usize, max_part: usize, current: &mut Vec<usize>, res: &mut Vec<Vec<usize>>) {
+    if n == 0 {
+        res.push(current.clone());
+        return;
+    }
+    for part in (1..=std::cmp::min(n, max_part)).rev() {
+        current.push(part);
+        partitions_of(n - part, part, current, res);
+        current.pop();
+    }
+}
+
+fn category_name(groups: &[usize]) -> String {
+    let num_pairs = groups.iter().filter(|&&x| x == 2).count();
+    match groups[0] {
+        1 => "High card".to_string(),
+        2 if num_pairs == 1 => "One pair".to_string(),
+        2 if num_pairs == 2 && groups.len() == 3 => "Two pair".to_string(),
+        3 if groups.get(1) == Some(&2) && groups.len() == 2 => "Full house".to_string(),
+        _ if groups.iter().skip(1).all(|&x| x == 1) => {
+            format!("{} of a kind", groups[0])
+        }
+        _ => groups
+            .iter()
+            .filter(|&&x| x > 1)
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join("+"),
+    }
+}
+
+/// A hand played under some rules. Hands are ordered by category, then
+/// card by card. Only hands played under the same rules can be compared.
+#[derive(Clone, Debug)]
+pub struct Hand<'a> {
+    pub cards: Vec<char>,
+    pub category: usize,
+    values: Vec<usize>,
+    rules: &'a CamelRules,
+}
+
+impl<'a> Hand<'a> {
+    pub fn new(cards: &str, rules: &'a CamelRules) -> Hand<'a> {
+        let cards = cards.chars().collect::<Vec<char>>();
+        assert_eq!(
+            cards.len(),
+            rules.hand_size,
+            "Hand {:?} doesn't have {} cards",
+            cards,
+            rules.hand_size
+        );
+        Hand {
+            category: rules.get_category(&cards),
+            values: cards.iter().map(|&card| rules.card_value(card)).collect(),
+            cards,
+            rules,
+        }
+    }
+
+    pub fn category_name(&self) -> &str {
+        &self.rules.categories[self.category].name
+    }
+
+    pub fn substitutes(&self) -> Vec<(char, char)> {
+        self.rules.get_substitutes(&self.cards, self.category)
+    }
+}
+
+impl PartialEq for Hand<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Hand<'_> {}
+
+impl PartialOrd for Hand<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.category
+            .cmp(&other.category)
+            .then_with(|| self.values.cmp(&other.values))
+    }
+}
+
+pub fn read_hands<'a>(s: &str, rules: &'a CamelRules) -> Vec<(Hand<'a>, i64)> {
+    s.lines()
+        .map(|line| {
+            let (hand, bid) = line.split_once(' ').unwrap();
+            (Hand::new(hand, rules), bid.trim().parse::<i64>().unwrap())
+        })
+        .collect()
+}
+
+/// Rank the hands from weakest to strongest. Identical hands are ranked in
+/// input order, so the hand appearing first gets the lower rank.
+pub fn rank_hands<'a>(mut hands: Vec<(Hand<'a>, i64)>) -> Vec<(Hand<'a>, i64)> {
+    // The sort is stable, which gives the tie-break for identical hands.
+    hands.sort_by(|(hand, _), (other, _)| hand.cmp(other));
+    hands
+}
+
+/// Print the category, wild card substitutions, rank and winnings of each
+/// hand, from the weakest to the strongest.
+pub fn explain(ranked_hands: &[(Hand, i64)]) {
+    for (i, (hand, bid)) in ranked_hands.iter().enumerate() {
+        let substitutes = hand
+            .substitutes()
+            .iter()
+            .map(|(wild, card)| format!("{}->{}", wild, card))
+            .collect::<Vec<String>>();
+        let substitutes = if substitutes.is_empty() {
+            "-".to_string()
+        } else {
+            substitutes.join(",")
+        };
+        println!(
+            "{} {:<15} {:<10} rank {:>5} bid {:>5} winnings {}",
+            hand.cards.iter().collect::<String>(),
+            hand.category_name(),
+            substitutes,
+            i + 1,
+            bid,
+            (i + 1) as i64 * bid
+        );
+    }
+}
+
+pub fn total_winnings(rules: &CamelRules, input: &str) -> i64 {
+    let hands = rank_hands(read_hands(input, rules));
+    if std::env::args().any(|arg| arg == "--explain") {
+        explain(&hands);
+    }
+    hands
+        .iter()
+        .enumerate()
+        .map(|(i, (_, bid))| (i + 1) as i64 * bid)
+        .sum::<i64>()
+}
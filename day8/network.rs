@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+/// Options given on the command line: `--start <glob>` and `--end <glob>`
+/// select the start and end nodes, and `--alphabet <chars>` gives the
+/// instruction taking each branch of a node, in order.
+pub struct Options {
+    pub start: String,
+    pub end: String,
+    pub alphabet: Vec<char>,
+}
+
+pub fn read_options(default_start: &str, default_end: &str) -> Options {
+    let args = std::env::args().collect::<Vec<String>>();
+    let value = |name: &str| {
+        let idx = args.iter().position(|arg| arg == name)?;
+        Some(
+            args.get(idx + 1)
+                .unwrap_or_else(|| panic!("{} expects a value", name))
+                .clone(),
+        )
+    };
+    Options {
+        start: value("--start").unwrap_or(default_start.to_string()),
+        end: value("--end").unwrap_or(default_end.to_string()),
+        alphabet: value("--alphabet")
+            .unwrap_or("LR".to_string())
+            .chars()
+            .collect(),
+    }
+}
+
+/// Match a node name against a glob pattern, where `?` matches any
+/// character and `*` any sequence of characters.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+    // matches[j] is whether the pattern read so far matches name[..j].
+    let mut matches = vec![false; name.len() + 1];
+    matches[0] = true;
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matches[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matches[j - 1],
+                _ => j > 0 && matches[j - 1] && name[j - 1] == p,
+            };
+        }
+        matches = next;
+    }
+    matches[name.len()]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtendedNode {
+    pub position: i32,
+    pub instruction: i32,
+}
+
+/// The network, where each node has one branch per instruction of the
+/// alphabet. Instructions are stored as the index of the branch to take.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    pub start_nodes: Vec<i32>,
+    pub end_nodes: Vec<i32>,
+    pub instructions: Vec<usize>,
+    pub nodes: Vec<Vec<i32>>,
+    #[allow(dead_code)]
+    pub names: Vec<String>,
+}
+
+impl Graph {
+    pub fn get_next_node(&self, extended_node: ExtendedNode) -> ExtendedNode {
+        let node = extended_node.position;
+        let instruction = extended_node.instruction;
+        let next_instruction: i32 = (instruction + 1) % (self.instructions.len() as i32);
+        let position = self.nodes[node as usize][self.instructions[instruction as usize]];
+        ExtendedNode {
+            position,
+            instruction: next_instruction,
+        }
+    }
+
+    pub fn is_end_node(&self, node: ExtendedNode) -> bool {
+        self.end_nodes.contains(&node.position)
+    }
+}
+
+fn parse_line(line: &str) -> Result<(&str, Vec<&str>), String> {
+    let (name, branches) = line
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid node line {:?}", line))?;
+    let branches = branches
+        .trim()
+        .strip_prefix('(')
+        .and_then(|x| x.strip_suffix(')'))
+        .ok_or_else(|| format!("Invalid node line {:?}", line))?;
+    Ok((name.trim(), branches.split(',').map(|x| x.trim()).collect()))
+}
+
+fn parse_instructions(file: &str, alphabet: &[char]) -> Result<Vec<usize>, String> {
+    file.lines()
+        .next()
+        .unwrap_or("")
+        .chars()
+        .enumerate()
+        .map(|(idx, c)| {
+            alphabet.iter().position(|&x| x == c).ok_or_else(|| {
+                format!(
+                    "Unknown instruction {:?} at position {}, expected one of {:?}",
+                    c,
+                    idx,
+                    alphabet.iter().collect::<String>()
+                )
+            })
+        })
+        .collect()
+}
+
+pub fn parse_graph(file: &str, options: &Options) -> Result<Graph, String> {
+    let instructions = parse_instructions(file, &options.alphabet)?;
+    if instructions.is_empty() {
+        return Err("No instructions".to_string());
+    }
+    let nodes = file
+        .lines()
+        .skip(2)
+        .map(parse_line)
+        .collect::<Result<Vec<_>, String>>()?;
+    let names_to_node: HashMap<_, _> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (k, _))| (*k, i as i32))
+        .collect();
+    let find_nodes = |pattern: &str| -> Vec<i32> {
+        nodes
+            .iter()
+            .filter(|(n, _)| glob_match(pattern, n))
+            .map(|(k, _)| names_to_node[*k])
+            .collect()
+    };
+    let start_nodes = find_nodes(&options.start);
+    let end_nodes = find_nodes(&options.end);
+    if start_nodes.is_empty() {
+        return Err(format!(
+            "No node matches the start pattern {:?}",
+            options.start
+        ));
+    }
+
+    let mut branches = Vec::new();
+    for (name, children) in &nodes {
+        if children.len() != options.alphabet.len() {
+            return Err(format!(
+                "Node {} has {} branches, expected {}",
+                name,
+                children.len(),
+                options.alphabet.len()
+            ));
+        }
+        branches.push(
+            children
+                .iter()
+                .map(|child| {
+                    names_to_node
+                        .get(child)
+                        .copied()
+                        .ok_or_else(|| format!("Node {} points to unknown node {}", name, child))
+                })
+                .collect::<Result<Vec<i32>, String>>()?,
+        );
+    }
+
+    Ok(Graph {
+        start_nodes,
+        end_nodes,
+        instructions,
+        nodes: branches,
+        names: nodes.iter().map(|(k, _)| k.to_string()).collect(),
+    })
+}
@@ -1,6 +1,7 @@
-use std::collections::{HashMap, VecDeque};
+mod network;
 
-use itertools::Itertools;
+use network::{parse_graph, read_options, ExtendedNode, Graph};
+use std::collections::{HashMap, HashSet};
 
 const FILENAME: &'static str = "day8/part1.in";
 
@@ -8,63 +9,31 @@ fn read_file() -> String {
     std::fs::read_to_string(FILENAME).expect("Something went wrong reading the file")
 }
 
-fn parse_line(line: &str) -> (&str, &str, &str) {
-    let (a, _, b, c) = line.split_whitespace().collect_tuple().unwrap();
-    (a, &b[1..b.len() - 1], &c[..c.len() - 1])
-}
-
+/// The steps at which a ghost is on an end node. After `offset` steps, the
+/// ghost loops over a cycle of `length` steps. `pre_cycle_hits` are the
+/// steps before the cycle starts, and `cycle_hits` the steps of the first
+/// iteration of the cycle, which then repeat every `length` steps.
 #[derive(Debug, Clone)]
-enum Direction {
-    Left,
-    Right,
+struct GhostCycle {
+    offset: i64,
+    length: i64,
+    pre_cycle_hits: Vec<i64>,
+    cycle_hits: Vec<i64>,
 }
 
-fn parse_instructions(file: &str) -> Vec<Direction> {
-    file.lines()
-        .nth(0)
-        .unwrap()
-        .chars()
-        .map(|c| {
-            if (c == 'L') {
-                Direction::Left
-            } else {
-                Direction::Right
-            }
-        })
-        .collect()
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct ExtendedNode {
-    position: i32,
-    instruction: i32,
-}
-
-#[derive(Debug, Clone)]
-struct Graph {
-    start_nodes: Vec<i32>,
-    end_nodes: Vec<i32>,
-    instructions: Vec<Direction>,
-    nodes: Vec<(i32, i32)>,
-}
-
-impl Graph {
-    fn get_next_node(&self, extended_node: ExtendedNode) -> ExtendedNode {
-        let node = extended_node.position;
-        let instruction = extended_node.instruction;
-        let (left, right) = self.nodes[node as usize];
-        let next_instruction: i32 = (instruction + 1) % (self.instructions.len() as i32);
-        let position = match self.instructions[instruction as usize] {
-            Direction::Left => left,
-            Direction::Right => right,
-        };
-        ExtendedNode {
-            position,
-            instruction: next_instruction,
+impl GhostCycle {
+    fn is_hit(&self, step: i64) -> bool {
+        if step < self.offset {
+            self.pre_cycle_hits.contains(&step)
+        } else {
+            let step_in_cycle = self.offset + (step - self.offset) % self.length;
+            self.cycle_hits.contains(&step_in_cycle)
         }
     }
+}
 
-    fn get_cycle_for_node(&self, starting_node: i32) -> (i64, i64, Vec<i64>) {
+impl Graph {
+    fn get_cycle_for_node(&self, starting_node: i32) -> GhostCycle {
         let mut current_node = ExtendedNode {
             position: starting_node,
             instruction: 0,
@@ -82,81 +51,48 @@ impl Graph {
             current_node = next_node;
         }
         let next_node = self.get_next_node(current_node);
-        let offset = visited[&next_node];
-
-        let mut new_cycle = Vec::new();
-        for node in cycle.iter().skip(offset as usize) {
-            if self.end_nodes.contains(&node.position) {
-                new_cycle.push(visited[node] as i64);
-            }
-        }
-
-        (
-            offset as i64,
-            (cycle.len() - offset as usize) as i64,
-            new_cycle,
-        )
-    }
+        let offset = visited[&next_node] as i64;
 
-    fn get_cycles(&self) -> Vec<(i64, i64, Vec<i64>)> {
-        let mut cycles = vec![];
-        for node in self.start_nodes.iter() {
-            let cycle = self.get_cycle_for_node(*node);
-            cycles.push(cycle);
+        let hits = cycle
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| self.is_end_node(**node))
+            .map(|(step, _)| step as i64);
+        let (pre_cycle_hits, cycle_hits) = hits.partition(|step| *step < offset);
+
+        GhostCycle {
+            offset,
+            length: cycle.len() as i64 - offset,
+            pre_cycle_hits,
+            cycle_hits,
         }
-        cycles
-    }
-
-    fn is_end_node(&self, node: ExtendedNode) -> bool {
-        self.end_nodes.contains(&node.position)
     }
-}
 
-fn parse_graph(file: &str) -> Graph {
-    let instructions = parse_instructions(file);
-    let nodes: Vec<(&str, &str, &str)> = file.lines().skip(2).map(parse_line).collect();
-    let names_to_node: HashMap<_, _> = nodes
-        .iter()
-        .enumerate()
-        .map(|(i, (k, _, _))| (*k, i as i32))
-        .collect();
-    let start_nodes = nodes
-        .iter()
-        .filter(|(n, _, _)| n.chars().nth(2).unwrap() == 'A')
-        .map(|(k, _, _)| names_to_node[*k])
-        .collect();
-    let end_nodes = nodes
-        .iter()
-        .filter(|(n, _, _)| n.chars().nth(2).unwrap() == 'Z')
-        .map(|(k, _, _)| names_to_node[*k])
-        .collect();
-    Graph {
-        start_nodes,
-        end_nodes,
-        instructions,
-        nodes: nodes
+    fn get_cycles(&self) -> Vec<GhostCycle> {
+        self.start_nodes
             .iter()
-            .map(|(k, l, r)| (names_to_node[*l], names_to_node[*r]))
-            .collect(),
+            .map(|node| self.get_cycle_for_node(*node))
+            .collect()
     }
-}
 
-fn is_finished(state: &Vec<(i64, VecDeque<i64>)>) -> bool {
-    let min = state.iter().map(|v| v.1[0]).min().unwrap();
-    let n = state.iter().filter(|v| v.1[0] == min).count();
-    if n > 4 {
-        println!("{} {}", n, min);
-    }
-    state.iter().map(|v| v.1[0]).all_equal()
-}
-
-fn next_stop(state: &mut Vec<(i64, VecDeque<i64>)>) {
-    let maxi = state.iter().map(|v| v.1[0]).max().unwrap();
-    for (size, v) in state.iter_mut() {
-        while v[0] < maxi {
-            let removed = v.pop_front().unwrap();
-            v.push_back(removed + *size);
+    /// Positions visited by a walk from any start node, in the graph of
+    /// (position, instruction index) pairs.
+    fn get_reachable_positions(&self) -> HashSet<i32> {
+        let mut visited = HashSet::new();
+        let mut stack = self
+            .start_nodes
+            .iter()
+            .map(|&position| ExtendedNode {
+                position,
+                instruction: 0,
+            })
+            .collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            if visited.insert(node) {
+                stack.push(self.get_next_node(node));
+            }
         }
+        visited.iter().map(|node| node.position).collect()
     }
 }
 
@@ -176,26 +112,107 @@ fn extended_gcd(x: i128, y: i128) -> (i128, i128, i128) {
     (old_r, old_s, old_t)
 }
 
-fn main() {
-    let graph = parse_graph(&read_file());
+/// Intersect the classes `x = a mod n` and `x = b mod m`, which don't need
+/// to be coprime. Return `None` if the intersection is empty.
+fn combine_residues((a, n): (i128, i128), (b, m): (i128, i128)) -> Option<(i128, i128)> {
+    let (gcd, u, _) = extended_gcd(n, m);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+    let lcm = n / gcd * m;
+    // u * n = gcd mod m, so a + n * u * (b - a) / gcd = b mod m.
+    let k = ((b - a) / gcd % (m / gcd)) * (u % (m / gcd)) % (m / gcd);
+    Some(((a + n * k).rem_euclid(lcm), lcm))
+}
 
-    use std::time::Instant;
-    let now = Instant::now();
+/// Earliest step at which all ghosts are on an end node at the same time,
+/// or `None` if they never are.
+fn earliest_common_step(cycles: &[GhostCycle]) -> Option<i64> {
+    // A common step before every ghost is in its cycle is a pre-cycle hit
+    // of the ghost with the longest tail.
+    let longest_tail = cycles.iter().max_by_key(|cycle| cycle.offset)?;
+    for step in &longest_tail.pre_cycle_hits {
+        if cycles.iter().all(|cycle| cycle.is_hit(*step)) {
+            return Some(*step);
+        }
+    }
 
-    // Code block to measure.
-    {
-        let cycles = graph.get_cycles();
-        let mut dequeue_cycles: Vec<_> = cycles
-            .iter()
-            .map(|(_, size, cycle)| (*size, VecDeque::from(cycle.clone())))
-            .collect();
+    // Otherwise, each ghost constrains the step to one of its cycle hits,
+    // modulo its cycle length.
+    let mut classes: HashSet<(i128, i128)> = HashSet::from([(0, 1)]);
+    for cycle in cycles {
+        let mut new_classes = HashSet::new();
+        for class in &classes {
+            for hit in &cycle.cycle_hits {
+                let ghost_class = (
+                    (*hit as i128).rem_euclid(cycle.length as i128),
+                    cycle.length as i128,
+                );
+                if let Some(new_class) = combine_residues(*class, ghost_class) {
+                    new_classes.insert(new_class);
+                }
+            }
+        }
+        classes = new_classes;
+    }
+
+    let start = longest_tail.offset as i128;
+    classes
+        .iter()
+        .map(|(residue, modulo)| {
+            // Smallest step at least `start` in the class.
+            residue + (start - residue + modulo - 1).div_euclid(*modulo) * modulo
+        })
+        .min()
+        .map(|step| i64::try_from(step).expect("Common step doesn't fit in an i64"))
+}
 
-        while !is_finished(&dequeue_cycles) {
-            next_stop(&mut dequeue_cycles);
-            // println!("{}", dequeue_cycles[0].1[0]);
+/// Print the structure of the walks: the unreachable nodes, the starts that
+/// never reach an end, the tail and cycle of each start, and whether the
+/// first hit of each walk is exactly its cycle length.
+fn print_analysis(graph: &Graph, cycles: &[GhostCycle]) {
+    let reachable = graph.get_reachable_positions();
+    let unreachable = (0..graph.nodes.len() as i32)
+        .filter(|position| !reachable.contains(position))
+        .map(|position| graph.names[position as usize].as_str())
+        .collect::<Vec<&str>>();
+    println!(
+        "{} of {} nodes are unreachable from any start: {}",
+        unreachable.len(),
+        graph.nodes.len(),
+        unreachable.join(" ")
+    );
+
+    let mut shortcut_holds = true;
+    for (start, cycle) in graph.start_nodes.iter().zip(cycles) {
+        let name = &graph.names[*start as usize];
+        let first_hit = cycle.pre_cycle_hits.iter().chain(&cycle.cycle_hits).next();
+        if first_hit.is_none() {
+            println!("{}: never reaches an end node", name);
         }
-        println!("{:?}", dequeue_cycles);
+        println!(
+            "{}: tail of {} steps, cycle of {} steps, hits before the cycle {:?}, hits in the cycle {:?}",
+            name, cycle.offset, cycle.length, cycle.pre_cycle_hits, cycle.cycle_hits
+        );
+        shortcut_holds &= cycle.pre_cycle_hits.is_empty() && cycle.cycle_hits == [cycle.length];
+    }
+    println!(
+        "Every walk hits an end node exactly once per cycle, at a step equal to the cycle length: {}",
+        if shortcut_holds { "yes" } else { "no" }
+    );
+}
+
+fn main() {
+    let graph = parse_graph(&read_file(), &read_options("??A", "??Z")).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1)
+    });
+    let cycles = graph.get_cycles();
+    if std::env::args().any(|arg| arg == "--analyze") {
+        print_analysis(&graph, &cycles);
+    }
+    match earliest_common_step(&cycles) {
+        Some(step) => println!("{}", step),
+        None => println!("never"),
     }
-    let elapsed = now.elapsed();
-    println!("Elapsed: {:.2?}", elapsed);
 }
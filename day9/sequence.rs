@@ -0,0 +1,301 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// A polynomial sequence, stored as its Newton forward differences at
+/// index 0: `differences[j]` is the j-th difference of the first value.
+/// The value at index `k` is then the sum of `differences[j] * C(k, j)`.
+pub struct Polynomial {
+    differences: Vec<i128>,
+}
+
+fn derivative(v: &[i128]) -> Vec<i128> {
+    v.windows(2)
+        .map(|w| w[1].checked_sub(w[0]).expect("Difference overflow"))
+        .collect()
+}
+
+impl Polynomial {
+    /// Fit the polynomial of minimal degree going through the values, which
+    /// are the terms of indices 0, 1, 2, ... of the sequence. Values that
+    /// never reach a constant difference are fitted with the polynomial of
+    /// degree `values.len() - 1`.
+    pub fn fit(values: &[i128]) -> Polynomial {
+        let mut differences = Vec::new();
+        let mut current = values.to_vec();
+        while !current.is_empty() && !current.iter().all(|&x| x == 0) {
+            differences.push(current[0]);
+            current = derivative(&current);
+        }
+        Polynomial { differences }
+    }
+
+    /// Fit a polynomial only if the values determine it, that is if the
+    /// differences reach a row of zeros before running out of values.
+    pub fn try_fit(values: &[i128]) -> Option<Polynomial> {
+        let res = Polynomial::fit(values);
+        if res.differences.len() < values.len() {
+            Some(res)
+        } else {
+            None
+        }
+    }
+
+    /// The degree of the polynomial, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    /// The value at index `k`, which can be negative or past the values
+    /// used for fitting.
+    pub fn eval(&self, k: &BigInt) -> BigInt {
+        let mut res = BigInt::from(0);
+        // C(k, j), using the generalized binomial coefficient for negative k.
+        let mut binomial = BigInt::from(1);
+        for (j, difference) in self.differences.iter().enumerate() {
+            res += &binomial * difference;
+            binomial = binomial * (k - j) / (j + 1);
+        }
+        res
+    }
+}
+
+/// A geometric sequence `first * ratio^k`.
+pub struct Geometric {
+    first: BigRational,
+    ratio: BigRational,
+}
+
+impl Geometric {
+    /// Fit a geometric sequence with a ratio other than 0 and 1, checked on
+    /// at least one value besides the two defining it.
+    pub fn try_fit(values: &[i128]) -> Option<Geometric> {
+        if values.len() < 3 || values[0] == 0 {
+            return None;
+        }
+        let values = to_rationals(values);
+        let ratio = &values[1] / &values[0];
+        if ratio.is_zero() || ratio.is_one() {
+            return None;
+        }
+        if values.windows(2).any(|w| w[1] != &w[0] * &ratio) {
+            return None;
+        }
+        Some(Geometric {
+            first: values[0].clone(),
+            ratio,
+        })
+    }
+
+    pub fn eval(&self, k: &BigInt) -> BigRational {
+        let exponent = k.abs();
+        let mut power = BigRational::one();
+        let mut base = if k.is_negative() {
+            self.ratio.recip()
+        } else {
+            self.ratio.clone()
+        };
+        let mut bits = exponent.to_biguint().unwrap();
+        while !bits.is_zero() {
+            if bits.bit(0) {
+                power *= &base;
+            }
+            base = &base * &base;
+            bits >>= 1;
+        }
+        &self.first * power
+    }
+}
+
+/// A sequence satisfying `s[n] + c[1] * s[n - 1] + ... + c[L] * s[n - L] = 0`
+/// for every `n >= L`, where `coefficients` holds `c[1..=L]`.
+pub struct LinearRecurrence {
+    coefficients: Vec<BigRational>,
+    values: Vec<BigRational>,
+}
+
+impl LinearRecurrence {
+    /// Find the shortest recurrence generating the values with the
+    /// Berlekamp-Massey algorithm. The recurrence is only kept if it is
+    /// unique and checked on at least one value, that is if `2L < n`.
+    pub fn try_fit(values: &[i128]) -> Option<LinearRecurrence> {
+        let values = to_rationals(values);
+        let mut connection = vec![BigRational::one()];
+        let mut previous = vec![BigRational::one()];
+        let mut length = 0;
+        let mut shift = 1;
+        let mut previous_discrepancy = BigRational::one();
+        for n in 0..values.len() {
+            let discrepancy = (0..=length)
+                .map(|i| &connection[i] * &values[n - i])
+                .fold(BigRational::zero(), |acc, x| acc + x);
+            if discrepancy.is_zero() {
+                shift += 1;
+                continue;
+            }
+            let factor = &discrepancy / &previous_discrepancy;
+            let old_connection = connection.clone();
+            if connection.len() < previous.len() + shift {
+                connection.resize(previous.len() + shift, BigRational::zero());
+            }
+            for (i, coefficient) in previous.iter().enumerate() {
+                connection[i + shift] -= &factor * coefficient;
+            }
+            if 2 * length <= n {
+                length = n + 1 - length;
+                previous = old_connection;
+                previous_discrepancy = discrepancy;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+        }
+        connection.resize(length + 1, BigRational::zero());
+        if length == 0 || 2 * length >= values.len() || connection[length].is_zero() {
+            return None;
+        }
+        Some(LinearRecurrence {
+            coefficients: connection.into_iter().skip(1).collect(),
+            values,
+        })
+    }
+
+    /// The value at index `k`, computed by running the recurrence forward
+    /// or backward from the known values. This is linear in the distance
+    /// between `k` and the known values.
+    pub fn eval(&self, k: &BigInt) -> BigRational {
+        let n = self.values.len();
+        let length = self.coefficients.len();
+        if !k.is_negative() && *k < BigInt::from(n) {
+            return self.values[usize::try_from(k).unwrap()].clone();
+        }
+        let mut window = self.values.clone();
+        if k.is_positive() {
+            let steps = usize::try_from(k - n + 1).expect("Index is too far");
+            for _ in 0..steps {
+                let next = self
+                    .coefficients
+                    .iter()
+                    .zip(window.iter().rev())
+                    .fold(BigRational::zero(), |acc, (c, x)| acc - c * x);
+                window.remove(0);
+                window.push(next);
+            }
+            window.pop().unwrap()
+        } else {
+            let steps = usize::try_from(-k).expect("Index is too far");
+            window.truncate(length);
+            for _ in 0..steps {
+                // s[n - L] = -(s[n] + c[1] * s[n - 1] + ... + c[L - 1] * s[n - L + 1]) / c[L]
+                let next = self.coefficients[..length - 1]
+                    .iter()
+                    .zip(window.iter().rev().skip(1))
+                    .fold(window[length - 1].clone(), |acc, (c, x)| acc + c * x);
+                window.pop();
+                window.insert(0, -next / &self.coefficients[length - 1]);
+            }
+            window[0].clone()
+        }
+    }
+}
+
+fn to_rationals(values: &[i128]) -> Vec<BigRational> {
+    values
+        .iter()
+        .map(|&x| BigRational::from_integer(BigInt::from(x)))
+        .collect()
+}
+
+/// The kind of sequence recognised from its values.
+pub enum Model {
+    Polynomial(Polynomial),
+    Geometric(Geometric),
+    LinearRecurrence(LinearRecurrence),
+}
+
+impl Model {
+    /// Recognise the sequence, trying the simplest models first. Return
+    /// `None` if the values don't determine any of them.
+    pub fn classify(values: &[i128]) -> Option<Model> {
+        if let Some(polynomial) = Polynomial::try_fit(values) {
+            return Some(Model::Polynomial(polynomial));
+        }
+        if let Some(geometric) = Geometric::try_fit(values) {
+            return Some(Model::Geometric(geometric));
+        }
+        LinearRecurrence::try_fit(values).map(Model::LinearRecurrence)
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Model::Polynomial(polynomial) => match polynomial.degree() {
+                Some(degree) => format!("polynomial of degree {}", degree),
+                None => "zero".to_string(),
+            },
+            Model::Geometric(geometric) => format!("geometric of ratio {}", geometric.ratio),
+            Model::LinearRecurrence(recurrence) => format!(
+                "linear recurrence of order {}",
+                recurrence.coefficients.len()
+            ),
+        }
+    }
+
+    pub fn eval(&self, k: &BigInt) -> BigRational {
+        match self {
+            Model::Polynomial(polynomial) => BigRational::from_integer(polynomial.eval(k)),
+            Model::Geometric(geometric) => geometric.eval(k),
+            Model::LinearRecurrence(recurrence) => recurrence.eval(k),
+        }
+    }
+}
+
+/// Sum the values of every sequence at the index given by `index`, or
+/// report the sequences that can't be predicted. With `--classify`, also
+/// print the model of each sequence.
+pub fn solve(problems: &[Vec<i128>], index: impl Fn(&[i128]) -> BigInt) {
+    let classify = std::env::args().any(|arg| arg == "--classify");
+    let mut res = BigRational::zero();
+    let mut unpredictable = Vec::new();
+    for (line, values) in problems.iter().enumerate() {
+        match Model::classify(values) {
+            Some(model) => {
+                if classify {
+                    println!("Line {}: {}", line + 1, model.describe());
+                }
+                res += model.eval(&index(values));
+            }
+            None => {
+                if classify {
+                    println!("Line {}: unpredictable", line + 1);
+                }
+                unpredictable.push(line + 1);
+            }
+        }
+    }
+    if unpredictable.is_empty() {
+        println!("{}", res);
+    } else {
+        println!("unpredictable: lines {:?}", unpredictable);
+    }
+}
+
+pub fn read_problems(s: &str) -> Vec<Vec<i128>> {
+    s.lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|s| s.parse::<i128>().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+/// Parse the `--at <index>` argument, if any.
+pub fn read_index_query() -> Option<BigInt> {
+    let args = std::env::args().collect::<Vec<String>>();
+    let idx = args.iter().position(|arg| arg == "--at")?;
+    Some(
+        args.get(idx + 1)
+            .and_then(|x| x.parse::<BigInt>().ok())
+            .expect("--at expects an index"),
+    )
+}